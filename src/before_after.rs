@@ -0,0 +1,171 @@
+//! Parsing of two-sided fixtures with a configurable separator and default paths.
+
+use crate::{Fixture, trim_indent};
+
+/// Separator line between the halves of a before/after fixture, unless configured otherwise.
+const DEFAULT_SEPARATOR: &str = "=>";
+
+/// Parser for before/after fixtures, see [`parse_before_after`](crate::parse_before_after)
+/// for the default configuration.
+///
+/// The separator is a whole line (after removing the common indentation), so
+/// a custom one lets `=>` appear alone on a content line, e.g. in a match arm.
+/// Each half that has no `//-` headers is a single file at that side's default path.
+///
+/// # Example
+///
+/// ```
+/// use v_fixtures::BeforeAfter;
+///
+/// let (before, after) = BeforeAfter::new().separator("-- after --").before_path("/in.rs").after_path("/out.rs").parse(
+///     r#"
+///     match x {
+///         _
+///         =>
+///         0,
+///     }
+///     -- after --
+///     match x { _ => 0 }
+///     "#,
+/// );
+/// assert_eq!(before.files[0].path, "/in.rs");
+/// assert!(before.files[0].text.contains("    =>\n"));
+/// assert_eq!(after.files[0].path, "/out.rs");
+/// ```
+#[derive(Clone, Debug)]
+pub struct BeforeAfter {
+	separator: String,
+	before_path: String,
+	after_path: String,
+}
+
+impl Default for BeforeAfter {
+	fn default() -> Self {
+		Self {
+			separator: DEFAULT_SEPARATOR.to_owned(),
+			before_path: "/main.rs".to_owned(),
+			after_path: "/main.rs".to_owned(),
+		}
+	}
+}
+
+impl BeforeAfter {
+	/// A parser splitting on `=>` lines, with `/main.rs` as the default path of both halves.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Split the halves on lines equal to `separator` instead of `=>`.
+	pub fn separator(mut self, separator: &str) -> Self {
+		assert!(
+			!separator.is_empty() && !separator.contains('\n'),
+			"before/after separator must be a non-empty single line, got {separator:?}"
+		);
+		self.separator = separator.to_owned();
+		self
+	}
+
+	/// Default path for single-file halves on both sides.
+	pub fn default_path(self, path: &str) -> Self {
+		self.before_path(path).after_path(path)
+	}
+
+	/// Default path for a single-file before half.
+	pub fn before_path(mut self, path: &str) -> Self {
+		self.before_path = path.to_owned();
+		self
+	}
+
+	/// Default path for a single-file after half.
+	pub fn after_path(mut self, path: &str) -> Self {
+		self.after_path = path.to_owned();
+		self
+	}
+
+	/// Parse `fixture` into its (before, after) halves.
+	///
+	/// Panics unless there is exactly one separator line.
+	#[track_caller]
+	pub fn parse(&self, fixture: &str) -> (Fixture, Fixture) {
+		let (before, after) = self.split(fixture);
+		(
+			Fixture::parse_with_default_path(&before, &self.before_path),
+			Fixture::parse_with_default_path(&after, &self.after_path),
+		)
+	}
+
+	/// Parse `fixture` whose after half is a patch applied to the before half,
+	/// see [`parse_before_patch`](crate::parse_before_patch).
+	///
+	/// Panics unless there is exactly one separator line, or if the patch doesn't apply.
+	#[track_caller]
+	pub fn parse_patch(&self, fixture: &str) -> (Fixture, Fixture) {
+		let (before, patch) = self.split(fixture);
+		let before = Fixture::parse_with_default_path(&before, &self.before_path);
+		let after = before.apply_patch(&patch).unwrap_or_else(|e| panic!("failed to apply patch: {e}"));
+		(before, after)
+	}
+
+	/// Split `fixture` into the texts of its halves, with the common indentation removed.
+	#[track_caller]
+	fn split(&self, fixture: &str) -> (String, String) {
+		let fixture = trim_indent(fixture);
+		let separators: Vec<usize> = fixture
+			.split_inclusive('\n')
+			.scan(0, |offset, line| {
+				let start = *offset;
+				*offset += line.len();
+				Some((start, line))
+			})
+			.filter(|(_, line)| line.strip_suffix('\n').unwrap_or(line) == self.separator)
+			.map(|(start, _)| start)
+			.collect();
+		let [start] = separators[..] else {
+			panic!("expected exactly one `{}` separator in before/after fixture, found {}", self.separator, separators.len());
+		};
+		let end = (start + self.separator.len() + 1).min(fixture.len());
+		// The newline before the separator belongs to it, as in `\n=>\n`
+		let before = fixture[..start].strip_suffix('\n').unwrap_or(&fixture[..start]);
+		(before.to_owned(), fixture[end..].to_owned())
+	}
+
+	/// The line separating the halves.
+	pub(crate) fn separator_line(&self) -> &str {
+		&self.separator
+	}
+
+	/// Default path of a single-file after half.
+	pub(crate) fn after_default_path(&self) -> &str {
+		&self.after_path
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_default_matches_parse_before_after() {
+		let fixture = "\n    //- /a.txt\n    one\n    =>\n    two\n";
+		let (before, after) = BeforeAfter::new().parse(fixture);
+		// Like splitting on `\n=>\n`, the before half loses its last newline
+		assert_eq!(before, Fixture::parse("//- /a.txt\none"));
+		assert_eq!(after, Fixture::parse("two\n"));
+		assert_eq!(after.files[0].path, "/main.rs");
+	}
+
+	#[test]
+	fn test_custom_separator_and_paths() {
+		let parser = BeforeAfter::new().separator("---").default_path("/config.yaml");
+		let (before, after) = parser.parse("a: 1\n=>\n---\n//- /other.yaml\nb: 2\n");
+		assert_eq!(before.files[0].path, "/config.yaml");
+		assert_eq!(before.files[0].text, "a: 1\n=>");
+		assert_eq!(after.files[0].path, "/other.yaml");
+	}
+
+	#[test]
+	#[should_panic(expected = "expected exactly one `=>` separator in before/after fixture, found 2")]
+	fn test_repeated_separator_panics() {
+		BeforeAfter::new().parse("a\n=>\nb\n=>\nc\n");
+	}
+}
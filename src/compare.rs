@@ -0,0 +1,429 @@
+//! Full-report fixture comparison behind [`assert_fixture_eq`].
+//!
+//! Rather than stopping at the first difference, [`FixtureMismatch`] collects
+//! every missing, extra and differing file, and renders them together with a
+//! line diff for each differing file. [`CompareOptions`] relaxes what counts as equal.
+
+use std::{borrow::Cow, fmt::Write as _, io::IsTerminal as _};
+
+use crate::{
+	CompareMode, Fixture, PathFilter,
+	diff::{DEFAULT_CONTEXT, LineTag, line_hunks},
+};
+
+/// What counts as equal for [`FixtureMismatch::compare_with`] and [`assert_fixture_eq_with`].
+///
+/// By default, file contents must match exactly and both fixtures must have the same files.
+///
+/// # Example
+///
+/// ```
+/// use v_fixtures::{CompareOptions, Fixture, assert_fixture_eq_with};
+///
+/// let expected = Fixture::parse("//- /out.txt\nhello world\n");
+/// let actual = Fixture::parse("//- /out.txt\r\nhello  world\r\n//- /build.log\n...\n");
+/// let options = CompareOptions::new().ignore_whitespace().subset();
+/// assert_fixture_eq_with(&expected, &actual, &options);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CompareOptions {
+	ignore_whitespace: bool,
+	ignore_trailing_newline: bool,
+	ignore_line_endings: bool,
+	filter: PathFilter,
+	subset: bool,
+}
+
+impl CompareOptions {
+	/// Options for an exact comparison.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Consider contents equal if they have the same words, however they're separated.
+	///
+	/// This also ignores line endings and trailing newlines.
+	pub fn ignore_whitespace(mut self) -> Self {
+		self.ignore_whitespace = true;
+		self
+	}
+
+	/// Ignore whether files end with a newline (or several).
+	pub fn ignore_trailing_newline(mut self) -> Self {
+		self.ignore_trailing_newline = true;
+		self
+	}
+
+	/// Treat `\r\n` line endings like `\n`.
+	pub fn ignore_line_endings(mut self) -> Self {
+		self.ignore_line_endings = true;
+		self
+	}
+
+	/// Only compare files passing `filter`, on both sides.
+	///
+	/// The same filter can be passed to [`ReadOptions::filter`](crate::ReadOptions::filter).
+	pub fn filter(mut self, filter: PathFilter) -> Self {
+		self.filter = filter;
+		self
+	}
+
+	/// Leave out files matching the gitignore-style `glob`, on both sides.
+	pub fn ignore(mut self, glob: &str) -> Self {
+		self.filter = self.filter.exclude(glob);
+		self
+	}
+
+	/// Only require the expected files to be present: extra actual files are fine.
+	pub fn subset(mut self) -> Self {
+		self.subset = true;
+		self
+	}
+
+	/// `text` with the ignored line endings and trailing newlines normalized away.
+	fn normalize<'t>(&self, text: &'t str) -> Cow<'t, str> {
+		let mut text = Cow::Borrowed(text);
+		if self.ignore_line_endings && text.contains("\r\n") {
+			text = Cow::Owned(text.replace("\r\n", "\n"));
+		}
+		if self.ignore_trailing_newline {
+			// Exactly one newline at the end, so diffs don't mention it
+			let content = text.trim_end_matches('\n');
+			if content.is_empty() {
+				text = Cow::Borrowed("");
+			} else if content.len() + 1 != text.len() {
+				text = Cow::Owned(format!("{content}\n"));
+			}
+		}
+		text
+	}
+}
+
+/// Every difference between an expected and an actual fixture.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FixtureMismatch {
+	/// Paths in expected but not in actual
+	pub missing: Vec<String>,
+	/// Paths in actual but not in expected
+	pub extra: Vec<String>,
+	/// Files present on both sides with different content
+	pub differing: Vec<DifferingFile>,
+}
+
+/// A file whose expected and actual contents differ.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DifferingFile {
+	pub path: String,
+	pub expected: String,
+	pub actual: String,
+}
+
+/// What kind of difference a [`DifferingFile`] has, for pointing out easily-missed ones.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DifferenceKind {
+	/// Contents differ only in whether the file ends with a newline
+	TrailingNewline,
+	/// Contents differ only in whitespace (including line endings)
+	WhitespaceOnly,
+	/// Any other difference
+	Content,
+}
+
+impl FixtureMismatch {
+	/// Compare `expected` against `actual`.
+	///
+	/// Structured files are compared by value according to the expected file's
+	/// [`CompareMode`]; for those, [`DifferingFile`] holds the canonicalized contents.
+	pub fn compare(expected: &Fixture, actual: &Fixture) -> Self {
+		Self::compare_with(expected, actual, &CompareOptions::default())
+	}
+
+	/// Compare `expected` against `actual`, with what counts as equal set by `options`.
+	///
+	/// Differing files hold their contents with ignored line endings and trailing
+	/// newlines normalized, so the report only shows differences that matter.
+	pub fn compare_with(expected: &Fixture, actual: &Fixture, options: &CompareOptions) -> Self {
+		let mut mismatch = Self::default();
+		for expected_file in expected.files.iter().filter(|f| options.filter.matches(&f.path)) {
+			let Some(actual_file) = actual.file(&expected_file.path) else {
+				mismatch.missing.push(expected_file.path.clone());
+				continue;
+			};
+			let (expected_text, actual_text) = (options.normalize(&expected_file.text), options.normalize(&actual_file.text));
+			if actual_text == expected_text {
+				continue;
+			}
+			let mode = CompareMode::for_file(expected_file);
+			let (expected_text, actual_text) = match (mode.canonicalize(&expected_text), mode.canonicalize(&actual_text)) {
+				(Some(expected), Some(actual)) if expected == actual => continue,
+				// Diff the canonical forms, so only semantic differences show up
+				(Some(expected), Some(actual)) => (expected, actual),
+				_ => (expected_text.into_owned(), actual_text.into_owned()),
+			};
+			if options.ignore_whitespace && expected_text.split_whitespace().eq(actual_text.split_whitespace()) {
+				continue;
+			}
+			mismatch.differing.push(DifferingFile {
+				path: expected_file.path.clone(),
+				expected: expected_text,
+				actual: actual_text,
+			});
+		}
+		if !options.subset {
+			mismatch.extra = actual
+				.files
+				.iter()
+				.filter(|f| options.filter.matches(&f.path) && !expected.contains(&f.path))
+				.map(|f| f.path.clone())
+				.collect();
+		}
+		mismatch
+	}
+
+	/// True if the fixtures are equal.
+	pub fn is_empty(&self) -> bool {
+		self.missing.is_empty() && self.extra.is_empty() && self.differing.is_empty()
+	}
+
+	/// Render the full report: a summary of all missing, extra and differing files,
+	/// followed by a line diff (`-` expected, `+` actual) for each differing file.
+	///
+	/// With `color`, removed/added lines and headers use ANSI colors.
+	pub fn render(&self, color: bool) -> String {
+		let paint = |code: &str, text: &str| if color { format!("\x1b[{code}m{text}\x1b[0m") } else { text.to_owned() };
+
+		let mut out = String::new();
+		writeln!(
+			out,
+			"fixtures differ: {} missing, {} extra, {} differing",
+			self.missing.len(),
+			self.extra.len(),
+			self.differing.len()
+		)
+		.unwrap();
+
+		if !self.missing.is_empty() {
+			writeln!(out, "\nmissing (expected, not in actual):").unwrap();
+			for path in &self.missing {
+				writeln!(out, "  {}", paint("31", path)).unwrap();
+			}
+		}
+		if !self.extra.is_empty() {
+			writeln!(out, "\nextra (in actual, not expected):").unwrap();
+			for path in &self.extra {
+				writeln!(out, "  {}", paint("32", path)).unwrap();
+			}
+		}
+		if !self.differing.is_empty() {
+			writeln!(out, "\ndiffering:").unwrap();
+			for file in &self.differing {
+				match file.kind() {
+					DifferenceKind::TrailingNewline => writeln!(out, "  {} (only the trailing newline differs)", paint("33", &file.path)),
+					DifferenceKind::WhitespaceOnly => writeln!(out, "  {} (whitespace-only difference)", paint("33", &file.path)),
+					DifferenceKind::Content => writeln!(out, "  {}", paint("33", &file.path)),
+				}
+				.unwrap();
+			}
+		}
+
+		for file in &self.differing {
+			// Whitespace-only differences are invisible unless whitespace is shown
+			let show_whitespace = file.kind() == DifferenceKind::WhitespaceOnly;
+			writeln!(out, "\n{}", paint("1", &format!("--- {} (- expected, + actual)", file.path))).unwrap();
+			for hunk in line_hunks(&file.expected, &file.actual, DEFAULT_CONTEXT) {
+				let header = format!("@@ -{},{} +{},{} @@", hunk.old_start, hunk.old_len, hunk.new_start, hunk.new_len);
+				writeln!(out, "{}", paint("36", &header)).unwrap();
+				for line in hunk.lines {
+					let text = if show_whitespace { visible_whitespace(&line.text) } else { line.text };
+					match line.tag {
+						LineTag::Context => writeln!(out, " {text}"),
+						LineTag::Removed => writeln!(out, "{}", paint("31", &format!("-{text}"))),
+						LineTag::Added => writeln!(out, "{}", paint("32", &format!("+{text}"))),
+					}
+					.unwrap();
+					if line.missing_newline {
+						writeln!(out, "\\ No newline at end of file").unwrap();
+					}
+				}
+			}
+		}
+		out
+	}
+}
+
+impl DifferingFile {
+	/// Classify the difference.
+	pub fn kind(&self) -> DifferenceKind {
+		if self.expected.trim_end_matches('\n') == self.actual.trim_end_matches('\n') {
+			DifferenceKind::TrailingNewline
+		} else if self.expected.split_whitespace().eq(self.actual.split_whitespace()) {
+			DifferenceKind::WhitespaceOnly
+		} else {
+			DifferenceKind::Content
+		}
+	}
+}
+
+/// Make spaces, tabs and carriage returns visible.
+fn visible_whitespace(text: &str) -> String {
+	text.replace(' ', "·").replace('\t', "→").replace('\r', "␍")
+}
+
+/// Whether assertion output should be colored.
+///
+/// Honors `NO_COLOR` and `CLICOLOR_FORCE`; otherwise colors when stderr is a terminal.
+pub(crate) fn color_enabled() -> bool {
+	if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+		return false;
+	}
+	if std::env::var_os("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0") {
+		return true;
+	}
+	std::io::stderr().is_terminal()
+}
+
+/// Compare two fixtures for equality, with a full report on failure.
+///
+/// On mismatch, panics listing every missing, extra and differing file at once,
+/// with a line diff for each differing file. Whitespace-only and trailing-newline
+/// differences are pointed out explicitly.
+///
+/// Output is colored when stderr is a terminal; set `NO_COLOR` to disable or
+/// `CLICOLOR_FORCE` to force it.
+#[track_caller]
+pub fn assert_fixture_eq(expected: &Fixture, actual: &Fixture) {
+	let mismatch = FixtureMismatch::compare(expected, actual);
+	if !mismatch.is_empty() {
+		panic!("{}", mismatch.render(color_enabled()));
+	}
+}
+
+/// Like [`assert_fixture_eq`], with what counts as equal set by `options`.
+#[track_caller]
+pub fn assert_fixture_eq_with(expected: &Fixture, actual: &Fixture, options: &CompareOptions) {
+	let mismatch = FixtureMismatch::compare_with(expected, actual, options);
+	if !mismatch.is_empty() {
+		panic!("{}", mismatch.render(color_enabled()));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_report_lists_everything() {
+		let expected = Fixture::parse(
+			r#"
+//- /same.txt
+same
+//- /missing.txt
+gone
+//- /code.rs
+fn main() {
+    a();
+}
+//- /newline.txt
+text
+//- /spaces.txt
+a b
+"#,
+		);
+		let actual = Fixture::parse("//- /same.txt\nsame\n//- /code.rs\nfn main() {\n    b();\n}\n//- /newline.txt\ntext\n\n//- /spaces.txt\na  b\n//- /extra.txt\nnew\n");
+
+		let mismatch = FixtureMismatch::compare(&expected, &actual);
+		insta::assert_snapshot!(mismatch.render(false), @r"
+		fixtures differ: 1 missing, 1 extra, 3 differing
+
+		missing (expected, not in actual):
+		  /missing.txt
+
+		extra (in actual, not expected):
+		  /extra.txt
+
+		differing:
+		  /code.rs
+		  /newline.txt (only the trailing newline differs)
+		  /spaces.txt (whitespace-only difference)
+
+		--- /code.rs (- expected, + actual)
+		@@ -1,3 +1,3 @@
+		 fn main() {
+		-    a();
+		+    b();
+		 }
+
+		--- /newline.txt (- expected, + actual)
+		@@ -1,1 +1,2 @@
+		 text
+		+
+
+		--- /spaces.txt (- expected, + actual)
+		@@ -1,1 +1,1 @@
+		-a·b
+		+a··b
+		");
+	}
+
+	#[test]
+	fn test_report_color() {
+		let mismatch = FixtureMismatch::compare(&Fixture::parse("a\n"), &Fixture::parse("b\n"));
+		let rendered = mismatch.render(true);
+		assert!(rendered.contains("\x1b[31m-a\x1b[0m"));
+		assert!(rendered.contains("\x1b[32m+b\x1b[0m"));
+	}
+
+	#[test]
+	fn test_assert_fixture_eq_passes() {
+		let fixture = Fixture::parse("//- /a.txt\na\n//- /b.txt\nb\n");
+		assert_fixture_eq(&fixture, &fixture.clone());
+	}
+
+	#[test]
+	#[should_panic(expected = "fixtures differ: 0 missing, 1 extra, 1 differing")]
+	fn test_assert_fixture_eq_reports_all() {
+		let expected = Fixture::parse("//- /a.txt\na\n");
+		let actual = Fixture::parse("//- /a.txt\nchanged\n//- /b.txt\nb\n");
+		assert_fixture_eq(&expected, &actual);
+	}
+
+	#[test]
+	fn test_compare_options() {
+		let expected = Fixture::parse("//- /a.txt\none\ntwo\n//- /b.txt\nb\n//- /target/out\nx\n");
+		let mut actual = Fixture::parse("//- /a.txt\n//- /b.txt\nb  \n//- /target/out\ny\n//- /extra.txt\n");
+		actual.files[0].text = "one\r\ntwo".to_owned();
+		let differing = |options: CompareOptions| {
+			let mismatch = FixtureMismatch::compare_with(&expected, &actual, &options);
+			let mut paths: Vec<_> = mismatch.differing.iter().map(|f| f.path.clone()).collect();
+			paths.extend(mismatch.extra);
+			paths
+		};
+		assert_eq!(differing(CompareOptions::new()), ["/a.txt", "/b.txt", "/target/out", "/extra.txt"]);
+		assert_eq!(differing(CompareOptions::new().ignore_line_endings()), ["/a.txt", "/b.txt", "/target/out", "/extra.txt"]);
+		assert_eq!(
+			differing(CompareOptions::new().ignore_line_endings().ignore_trailing_newline().subset()),
+			["/b.txt", "/target/out"]
+		);
+		assert_eq!(differing(CompareOptions::new().ignore_whitespace().ignore("target/")), ["/extra.txt"]);
+
+		// Ignored differences don't show up in the report of the remaining ones
+		let mismatch = FixtureMismatch::compare_with(&expected, &actual, &CompareOptions::new().ignore_line_endings().ignore_trailing_newline());
+		assert_eq!(mismatch.differing[0].path, "/b.txt");
+		let missing = FixtureMismatch::compare_with(&actual, &expected, &CompareOptions::new().filter(PathFilter::new().include("*.txt")));
+		assert_eq!(missing.missing, ["/extra.txt"]);
+	}
+
+	#[cfg(feature = "json")]
+	#[test]
+	fn test_compare_structured_by_value() {
+		let expected = Fixture::parse("//- /a.json\n{\"x\": 1, \"y\": [1, 2]}\n//- /b.json compare:text\n{\"x\": 1}\n");
+		let actual = Fixture::parse("//- /a.json\n{ \"y\": [1, 2],\n  \"x\": 1 }\n//- /b.json\n{ \"x\": 1 }\n");
+		let mismatch = FixtureMismatch::compare(&expected, &actual);
+		assert_eq!(mismatch.differing.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(), ["/b.json"]);
+
+		let actual = Fixture::parse("//- /a.json\n{\"y\": [1, 3], \"x\": 1}\n//- /b.json\n{\"x\": 1}\n");
+		let mismatch = FixtureMismatch::compare(&expected, &actual);
+		assert_eq!(mismatch.differing[0].expected, "{\n  \"x\": 1,\n  \"y\": [\n    1,\n    2\n  ]\n}\n");
+		assert!(mismatch.render(false).contains("-    2\n+    3\n"));
+	}
+}
@@ -0,0 +1,161 @@
+//! Transparent on-disk compression for fixture files.
+//!
+//! A file declared as `//- /logs/app.log.gz compress:gzip` is written to disk
+//! gzip-compressed, while its `text` in the fixture stays plain. When files are
+//! read back, compressed content is detected by its magic bytes and decompressed,
+//! so snapshots show readable text.
+//!
+//! The codecs live behind the `gzip` and `zstd` features.
+
+/// Compression applied to a fixture file when it is stored on disk.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Compression {
+	Gzip,
+	Zstd,
+}
+
+impl Compression {
+	/// Parse the value of a `compress:` meta attribute.
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"gzip" | "gz" => Some(Self::Gzip),
+			"zstd" | "zst" => Some(Self::Zstd),
+			_ => None,
+		}
+	}
+
+	/// Whether the codec is compiled in.
+	pub(crate) fn enabled(self) -> bool {
+		match self {
+			Self::Gzip => gzip::ENABLED,
+			Self::Zstd => zstd_codec::ENABLED,
+		}
+	}
+
+	/// Name used in the `compress:` meta attribute.
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Gzip => "gzip",
+			Self::Zstd => "zstd",
+		}
+	}
+
+	/// Detect compressed content by its magic bytes.
+	pub fn detect(bytes: &[u8]) -> Option<Self> {
+		if bytes.starts_with(&[0x1f, 0x8b]) {
+			Some(Self::Gzip)
+		} else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+			Some(Self::Zstd)
+		} else {
+			None
+		}
+	}
+
+	/// Compress `text` for writing to disk.
+	///
+	/// Panics if the codec's feature is not enabled.
+	pub fn compress(self, text: &str) -> Vec<u8> {
+		match self {
+			Self::Gzip => gzip::compress(text),
+			Self::Zstd => zstd_codec::compress(text),
+		}
+	}
+
+	/// Decompress bytes read from disk into text.
+	///
+	/// Returns `None` if the content is not valid for this codec, is not UTF-8,
+	/// or the codec's feature is not enabled.
+	pub fn decompress(self, bytes: &[u8]) -> Option<String> {
+		let raw = match self {
+			Self::Gzip => gzip::decompress(bytes)?,
+			Self::Zstd => zstd_codec::decompress(bytes)?,
+		};
+		String::from_utf8(raw).ok()
+	}
+}
+
+#[cfg(feature = "gzip")]
+mod gzip {
+	use std::io::{Read as _, Write as _};
+
+	pub const ENABLED: bool = true;
+
+	pub fn compress(text: &str) -> Vec<u8> {
+		let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+		encoder.write_all(text.as_bytes()).expect("failed to gzip fixture file");
+		encoder.finish().expect("failed to gzip fixture file")
+	}
+
+	pub fn decompress(bytes: &[u8]) -> Option<Vec<u8>> {
+		let mut out = Vec::new();
+		flate2::read::MultiGzDecoder::new(bytes).read_to_end(&mut out).ok()?;
+		Some(out)
+	}
+}
+
+#[cfg(not(feature = "gzip"))]
+mod gzip {
+	pub const ENABLED: bool = false;
+
+	pub fn compress(_text: &str) -> Vec<u8> {
+		panic!("`compress:gzip` requires the `gzip` feature of v_fixtures")
+	}
+
+	pub fn decompress(_bytes: &[u8]) -> Option<Vec<u8>> {
+		None
+	}
+}
+
+#[cfg(feature = "zstd")]
+mod zstd_codec {
+	pub const ENABLED: bool = true;
+
+	pub fn compress(text: &str) -> Vec<u8> {
+		zstd::encode_all(text.as_bytes(), 0).expect("failed to zstd-compress fixture file")
+	}
+
+	pub fn decompress(bytes: &[u8]) -> Option<Vec<u8>> {
+		zstd::decode_all(bytes).ok()
+	}
+}
+
+#[cfg(not(feature = "zstd"))]
+mod zstd_codec {
+	pub const ENABLED: bool = false;
+
+	pub fn compress(_text: &str) -> Vec<u8> {
+		panic!("`compress:zstd` requires the `zstd` feature of v_fixtures")
+	}
+
+	pub fn decompress(_bytes: &[u8]) -> Option<Vec<u8>> {
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_detect_magic() {
+		assert_eq!(Compression::detect(&[0x1f, 0x8b, 0x08]), Some(Compression::Gzip));
+		assert_eq!(Compression::detect(&[0x28, 0xb5, 0x2f, 0xfd, 0x00]), Some(Compression::Zstd));
+		assert_eq!(Compression::detect(b"plain text"), None);
+	}
+
+	#[cfg(feature = "gzip")]
+	#[test]
+	fn test_gzip_roundtrip() {
+		let bytes = Compression::Gzip.compress("line 1\nline 2\n");
+		assert_eq!(Compression::detect(&bytes), Some(Compression::Gzip));
+		assert_eq!(Compression::Gzip.decompress(&bytes).unwrap(), "line 1\nline 2\n");
+	}
+
+	#[cfg(feature = "zstd")]
+	#[test]
+	fn test_zstd_roundtrip() {
+		let bytes = Compression::Zstd.compress("line 1\nline 2\n");
+		assert_eq!(Compression::detect(&bytes), Some(Compression::Zstd));
+		assert_eq!(Compression::Zstd.decompress(&bytes).unwrap(), "line 1\nline 2\n");
+	}
+}
@@ -0,0 +1,425 @@
+//! Structured comparison of two fixtures.
+//!
+//! [`Fixture::diff`] lists added, removed, modified and renamed files, with
+//! line hunks for every file whose content changed. A [`FixtureDiff`] renders
+//! as a `git diff`-style unified diff (which [`Fixture::apply_patch`] accepts)
+//! or as a one-line-per-file summary table.
+
+use std::fmt::{self, Write as _};
+
+use similar::{ChangeTag, TextDiff};
+
+use crate::Fixture;
+
+/// Number of context lines around changes used by [`Fixture::diff`].
+pub(crate) const DEFAULT_CONTEXT: usize = 3;
+
+/// Files below this line similarity are not considered renames of each other.
+const RENAME_THRESHOLD: f32 = 0.5;
+
+/// Differences between two fixtures, sorted by path.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FixtureDiff {
+	pub changes: Vec<FileChange>,
+}
+
+/// How a single file differs between two fixtures.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FileChange {
+	/// File only exists in the new fixture.
+	Added { path: String, hunks: Vec<DiffHunk> },
+	/// File only exists in the old fixture.
+	Removed { path: String, hunks: Vec<DiffHunk> },
+	/// File exists in both with different content.
+	Modified { path: String, hunks: Vec<DiffHunk> },
+	/// File moved to a new path, possibly with content changes.
+	Renamed { from: String, to: String, hunks: Vec<DiffHunk> },
+}
+
+/// A contiguous group of changed lines with surrounding context.
+///
+/// Line numbers are 1-indexed, as in a unified diff header. A side with
+/// zero length names the line *after which* the other side's lines go.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DiffHunk {
+	pub old_start: usize,
+	pub old_len: usize,
+	pub new_start: usize,
+	pub new_len: usize,
+	pub lines: Vec<DiffLine>,
+}
+
+/// A single line of a [`DiffHunk`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DiffLine {
+	pub tag: LineTag,
+	/// Line content without its line terminator
+	pub text: String,
+	/// This is the last line of its side and has no trailing newline
+	pub missing_newline: bool,
+}
+
+/// Whether a [`DiffLine`] is shared, removed or added.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LineTag {
+	Context,
+	Removed,
+	Added,
+}
+
+impl Fixture {
+	/// Compare this fixture (old) with `other` (new).
+	///
+	/// Only file texts are compared; meta attributes are ignored. A removed and
+	/// an added file whose contents are at least 50% similar are reported as a rename.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::{Fixture, FileChange};
+	///
+	/// let before = Fixture::parse("//- /a.txt\none\n//- /b.txt\ntwo\n");
+	/// let after = Fixture::parse("//- /a.txt\nONE\n//- /c.txt\nthree\n");
+	/// let diff = before.diff(&after);
+	///
+	/// assert!(matches!(&diff.changes[0], FileChange::Modified { path, .. } if path == "/a.txt"));
+	/// assert!(diff.render_unified().contains("+ONE"));
+	/// ```
+	pub fn diff(&self, other: &Fixture) -> FixtureDiff {
+		self.diff_with_context(other, DEFAULT_CONTEXT)
+	}
+
+	/// Like [`Fixture::diff`], with `context` lines of context around each hunk.
+	pub fn diff_with_context(&self, other: &Fixture, context: usize) -> FixtureDiff {
+		let mut changes = Vec::new();
+		let mut removed = Vec::new();
+		for old in &self.files {
+			match other.file(&old.path) {
+				Some(new) if new.text != old.text => changes.push(FileChange::Modified {
+					path: old.path.clone(),
+					hunks: line_hunks(&old.text, &new.text, context),
+				}),
+				Some(_) => {}
+				None => removed.push(old),
+			}
+		}
+		let added: Vec<_> = other.files.iter().filter(|f| !self.contains(&f.path)).collect();
+
+		// Pair up the most similar removed/added files as renames
+		let mut candidates = Vec::new();
+		for (r, old) in removed.iter().enumerate() {
+			for (a, new) in added.iter().enumerate() {
+				let ratio = TextDiff::from_lines(&old.text, &new.text).ratio();
+				if ratio >= RENAME_THRESHOLD && !(old.text.is_empty() && new.text.is_empty()) {
+					candidates.push((ratio, r, a));
+				}
+			}
+		}
+		candidates.sort_by(|x, y| y.0.total_cmp(&x.0));
+		let (mut renamed_old, mut renamed_new) = (vec![false; removed.len()], vec![false; added.len()]);
+		for (_, r, a) in candidates {
+			if renamed_old[r] || renamed_new[a] {
+				continue;
+			}
+			renamed_old[r] = true;
+			renamed_new[a] = true;
+			changes.push(FileChange::Renamed {
+				from: removed[r].path.clone(),
+				to: added[a].path.clone(),
+				hunks: line_hunks(&removed[r].text, &added[a].text, context),
+			});
+		}
+
+		for (f, _) in removed.into_iter().zip(renamed_old).filter(|(_, renamed)| !renamed) {
+			changes.push(FileChange::Removed {
+				path: f.path.clone(),
+				hunks: line_hunks(&f.text, "", context),
+			});
+		}
+		for (f, _) in added.into_iter().zip(renamed_new).filter(|(_, renamed)| !renamed) {
+			changes.push(FileChange::Added {
+				path: f.path.clone(),
+				hunks: line_hunks("", &f.text, context),
+			});
+		}
+
+		changes.sort_by(|x, y| x.path().cmp(y.path()));
+		FixtureDiff { changes }
+	}
+}
+
+impl FixtureDiff {
+	/// True if the fixtures have identical files.
+	pub fn is_empty(&self) -> bool {
+		self.changes.is_empty()
+	}
+
+	/// Render as a `git diff`-style unified diff.
+	///
+	/// The output can be fed back into [`Fixture::apply_patch`].
+	pub fn render_unified(&self) -> String {
+		let mut out = String::new();
+		for change in &self.changes {
+			change.write_unified(&mut out);
+		}
+		out
+	}
+
+	/// Render a table with one row per changed file: status, path and line counts.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::Fixture;
+	///
+	/// let before = Fixture::parse("//- /src/main.rs\nfn main() {\n    hello();\n}\n//- /src/lib.rs\npub fn a() {}\n");
+	/// let after = Fixture::parse("//- /src/main.rs\nfn main() {\n    bye();\n}\n//- /src/util.rs\npub fn a() {}\n");
+	/// assert_eq!(
+	///     before.diff(&after).render_summary(),
+	///     "\
+	/// status    path                         +  -
+	/// modified  /src/main.rs                 1  1
+	/// renamed   /src/lib.rs -> /src/util.rs  0  0
+	/// "
+	/// );
+	/// ```
+	pub fn render_summary(&self) -> String {
+		let rows: Vec<_> = self
+			.changes
+			.iter()
+			.map(|c| {
+				let path = match c {
+					FileChange::Renamed { from, to, .. } => format!("{from} -> {to}"),
+					_ => c.path().to_owned(),
+				};
+				let (added, removed) = c.line_counts();
+				(c.status(), path, added.to_string(), removed.to_string())
+			})
+			.collect();
+
+		let path_width = rows.iter().map(|r| r.1.len()).max().unwrap_or(0).max("path".len());
+		let added_width = rows.iter().map(|r| r.2.len()).max().unwrap_or(0).max(1);
+		let removed_width = rows.iter().map(|r| r.3.len()).max().unwrap_or(0).max(1);
+
+		let mut out = String::new();
+		writeln!(out, "{:<8}  {:<path_width$}  {:>added_width$}  {:>removed_width$}", "status", "path", "+", "-").unwrap();
+		for (status, path, added, removed) in rows {
+			writeln!(out, "{status:<8}  {path:<path_width$}  {added:>added_width$}  {removed:>removed_width$}").unwrap();
+		}
+		out
+	}
+}
+
+impl fmt::Display for FixtureDiff {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.render_unified())
+	}
+}
+
+impl FileChange {
+	/// Path of the file (the new path for renames).
+	pub fn path(&self) -> &str {
+		match self {
+			Self::Added { path, .. } | Self::Removed { path, .. } | Self::Modified { path, .. } => path,
+			Self::Renamed { to, .. } => to,
+		}
+	}
+
+	/// Line hunks of the change. Added and removed files have a single hunk covering the whole file.
+	pub fn hunks(&self) -> &[DiffHunk] {
+		match self {
+			Self::Added { hunks, .. } | Self::Removed { hunks, .. } | Self::Modified { hunks, .. } | Self::Renamed { hunks, .. } => hunks,
+		}
+	}
+
+	/// Short status word: `added`, `removed`, `modified` or `renamed`.
+	pub fn status(&self) -> &'static str {
+		match self {
+			Self::Added { .. } => "added",
+			Self::Removed { .. } => "removed",
+			Self::Modified { .. } => "modified",
+			Self::Renamed { .. } => "renamed",
+		}
+	}
+
+	/// Number of (added, removed) lines.
+	pub fn line_counts(&self) -> (usize, usize) {
+		let lines = self.hunks().iter().flat_map(|h| &h.lines);
+		lines.fold((0, 0), |(added, removed), l| match l.tag {
+			LineTag::Added => (added + 1, removed),
+			LineTag::Removed => (added, removed + 1),
+			LineTag::Context => (added, removed),
+		})
+	}
+
+	fn write_unified(&self, out: &mut String) {
+		let (old, new) = match self {
+			Self::Added { path, .. } | Self::Removed { path, .. } | Self::Modified { path, .. } => (path, path),
+			Self::Renamed { from, to, .. } => (from, to),
+		};
+		let (old, new) = (old.trim_start_matches('/'), new.trim_start_matches('/'));
+		writeln!(out, "diff --git a/{old} b/{new}").unwrap();
+		match self {
+			Self::Added { .. } => writeln!(out, "new file mode 100644").unwrap(),
+			Self::Removed { .. } => writeln!(out, "deleted file mode 100644").unwrap(),
+			Self::Renamed { .. } => writeln!(out, "rename from {old}\nrename to {new}").unwrap(),
+			Self::Modified { .. } => {}
+		}
+		if self.hunks().is_empty() {
+			return;
+		}
+		let old_side = if matches!(self, Self::Added { .. }) { "/dev/null".to_owned() } else { format!("a/{old}") };
+		let new_side = if matches!(self, Self::Removed { .. }) { "/dev/null".to_owned() } else { format!("b/{new}") };
+		writeln!(out, "--- {old_side}\n+++ {new_side}").unwrap();
+		for hunk in self.hunks() {
+			hunk.write_unified(out);
+		}
+	}
+}
+
+impl DiffHunk {
+	fn write_unified(&self, out: &mut String) {
+		writeln!(out, "@@ -{},{} +{},{} @@", self.old_start, self.old_len, self.new_start, self.new_len).unwrap();
+		for line in &self.lines {
+			let sign = match line.tag {
+				LineTag::Context => ' ',
+				LineTag::Removed => '-',
+				LineTag::Added => '+',
+			};
+			writeln!(out, "{sign}{}", line.text).unwrap();
+			if line.missing_newline {
+				out.push_str("\\ No newline at end of file\n");
+			}
+		}
+	}
+}
+
+/// Compute unified-diff hunks between two texts.
+pub(crate) fn line_hunks(old: &str, new: &str, context: usize) -> Vec<DiffHunk> {
+	let diff = TextDiff::from_lines(old, new);
+	diff.grouped_ops(context)
+		.into_iter()
+		.map(|group| {
+			let (first, last) = (group.first().expect("groups are non-empty"), group.last().expect("groups are non-empty"));
+			let (old_range, new_range) = (first.old_range().start..last.old_range().end, first.new_range().start..last.new_range().end);
+			let start = |range: &std::ops::Range<usize>| if range.is_empty() { range.start } else { range.start + 1 };
+			let lines = group
+				.iter()
+				.flat_map(|op| diff.iter_changes(op))
+				.map(|change| DiffLine {
+					tag: match change.tag() {
+						ChangeTag::Equal => LineTag::Context,
+						ChangeTag::Delete => LineTag::Removed,
+						ChangeTag::Insert => LineTag::Added,
+					},
+					text: change.value().strip_suffix('\n').unwrap_or(change.value()).to_owned(),
+					missing_newline: change.missing_newline(),
+				})
+				.collect();
+			DiffHunk {
+				old_start: start(&old_range),
+				old_len: old_range.len(),
+				new_start: start(&new_range),
+				new_len: new_range.len(),
+				lines,
+			}
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn before() -> Fixture {
+		Fixture::parse(
+			r#"
+//- /src/main.rs
+fn main() {
+    println!("hello");
+}
+//- /src/lib.rs
+pub fn a() {}
+pub fn b() {}
+pub fn c() {}
+//- /README.md
+# Project
+"#,
+		)
+	}
+
+	fn after() -> Fixture {
+		Fixture::parse(
+			r#"
+//- /src/main.rs
+fn main() {
+    println!("bye");
+}
+//- /src/util.rs
+pub fn a() {}
+pub fn b() {}
+pub fn c() {}
+pub fn d() {}
+//- /NOTES.md
+note
+"#,
+		)
+	}
+
+	#[test]
+	fn test_diff_classifies_changes() {
+		let diff = before().diff(&after());
+		let statuses: Vec<_> = diff.changes.iter().map(|c| (c.status(), c.path())).collect();
+		assert_eq!(
+			statuses,
+			[("added", "/NOTES.md"), ("removed", "/README.md"), ("modified", "/src/main.rs"), ("renamed", "/src/util.rs")]
+		);
+
+		let FileChange::Modified { hunks, .. } = &diff.changes[2] else { unreachable!() };
+		assert_eq!(hunks.len(), 1);
+		assert_eq!((hunks[0].old_start, hunks[0].old_len, hunks[0].new_start, hunks[0].new_len), (1, 3, 1, 3));
+		assert_eq!(diff.changes[3].line_counts(), (1, 0));
+	}
+
+	#[test]
+	fn test_diff_identical_is_empty() {
+		assert!(before().diff(&before()).is_empty());
+	}
+
+	#[test]
+	fn test_unified_roundtrips_through_apply_patch() {
+		let diff = before().diff(&after());
+		let mut patched = before().apply_patch(&diff.render_unified()).unwrap();
+		let mut expected = after();
+		patched.files.sort_by(|a, b| a.path.cmp(&b.path));
+		expected.files.sort_by(|a, b| a.path.cmp(&b.path));
+		assert_eq!(patched, expected);
+	}
+
+	#[test]
+	fn test_unified_missing_newline() {
+		let old = Fixture::parse("//- /a.txt\none\ntwo");
+		let new = Fixture::parse("//- /a.txt\none\ntwo\n");
+		insta::assert_snapshot!(old.diff(&new).render_unified(), @r"
+		diff --git a/a.txt b/a.txt
+		--- a/a.txt
+		+++ b/a.txt
+		@@ -1,2 +1,2 @@
+		 one
+		-two
+		\ No newline at end of file
+		+two
+		");
+	}
+
+	#[test]
+	fn test_render_summary() {
+		insta::assert_snapshot!(before().diff(&after()).render_summary(), @r"
+		status    path                         +  -
+		added     /NOTES.md                    1  0
+		removed   /README.md                   0  1
+		modified  /src/main.rs                 1  1
+		renamed   /src/lib.rs -> /src/util.rs  1  0
+		");
+	}
+}
@@ -0,0 +1,364 @@
+//! Self-updating before/after expectations, in the style of `expect-test`.
+//!
+//! [`assert_after_eq`] compares a fixture against the `=>` half of a
+//! before/after literal ([`BeforeAfter::assert_after_eq`] for other
+//! separators). When it fails with `UPDATE_EXPECT=1` set, the `=>` half is
+//! rewritten in the test's source file instead, keeping the literal's
+//! indentation and adding raw-string hashes if the new content needs them.
+//!
+//! Only string literals whose contents appear verbatim in the source (raw
+//! strings, or plain strings without escapes) can be updated.
+
+use std::{
+	collections::BTreeMap,
+	panic::Location,
+	path::{Path, PathBuf},
+	sync::Mutex,
+};
+
+use crate::{BeforeAfter, Fixture, FixtureMismatch, compare::color_enabled};
+
+/// Environment variable that turns failing expectations into source updates.
+pub const UPDATE_EXPECT_VAR: &str = "UPDATE_EXPECT";
+
+/// Serializes source rewrites from tests running in parallel, and remembers
+/// how each rewrite moved the lines of its file.
+static SOURCE_EDITS: Mutex<BTreeMap<PathBuf, Vec<LineShift>>> = Mutex::new(BTreeMap::new());
+
+/// Lines added (or removed) by a rewrite after the last line it changed.
+#[derive(Clone, Copy, Debug)]
+struct LineShift {
+	last_changed: usize,
+	delta: isize,
+}
+
+/// Assert that `actual` equals the `=>` half of the before/after `fixture`.
+///
+/// With `UPDATE_EXPECT=1`, a mismatch rewrites the `=>` half of the fixture
+/// literal in the caller's source file with `actual` instead of panicking.
+///
+/// # Example
+///
+/// ```
+/// use v_fixtures::{assert_after_eq, parse_before_after};
+///
+/// let fixture = r#"
+///     //- /greeting.txt
+///     hello
+///     =>
+///     //- /greeting.txt
+///     HELLO
+/// "#;
+/// let (before, _) = parse_before_after(fixture);
+/// let temp = before.write_to_tempdir();
+///
+/// // ... run the tool under test ...
+/// temp.write("/greeting.txt", "HELLO\n");
+///
+/// assert_after_eq(fixture, &temp.read_all_from_disk());
+/// ```
+#[track_caller]
+pub fn assert_after_eq(fixture: &str, actual: &Fixture) {
+	BeforeAfter::new().assert_after_eq(fixture, actual);
+}
+
+impl BeforeAfter {
+	/// Like [`assert_after_eq`], for fixtures in this parser's format.
+	///
+	/// Updates keep the configured separator, and a single file at the after
+	/// half's default path is written without a header.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::{BeforeAfter, Fixture};
+	///
+	/// let parser = BeforeAfter::new().separator("---").default_path("/out.txt");
+	/// parser.assert_after_eq("before\n---\nafter\n", &Fixture::parse_with_default_path("after\n", "/out.txt"));
+	/// ```
+	#[track_caller]
+	pub fn assert_after_eq(&self, fixture: &str, actual: &Fixture) {
+		assert_after_eq_with(self, fixture, actual);
+	}
+}
+
+#[track_caller]
+fn assert_after_eq_with(parser: &BeforeAfter, fixture: &str, actual: &Fixture) {
+	let (_, expected) = parser.parse(fixture);
+	let mismatch = FixtureMismatch::compare(&expected, actual);
+	if mismatch.is_empty() {
+		return;
+	}
+
+	let caller = Location::caller();
+	if update_requested() {
+		let mut edits = SOURCE_EDITS.lock().unwrap_or_else(|e| e.into_inner());
+		let path = resolve_source_path(caller.file());
+		let source = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("failed to read {}: {e}", path.display()));
+		let rendered = render_expectation(actual, parser.after_default_path());
+		// The caller's line is from compile time; earlier updates may have moved it
+		let shifts = edits.entry(path.clone()).or_default();
+		let line = current_line(shifts, caller.line() as usize);
+		let updated = rewrite_after_half(&source, fixture, line, &rendered, parser.separator_line())
+			.unwrap_or_else(|e| panic!("cannot update expectation at {}:{}: {e}", caller.file(), caller.line()));
+		std::fs::write(&path, &updated).unwrap_or_else(|e| panic!("failed to write {}: {e}", path.display()));
+		shifts.push(line_shift(&source, &updated));
+		eprintln!("updated expectation at {}:{}", caller.file(), caller.line());
+		return;
+	}
+
+	panic!(
+		"{}\nset {UPDATE_EXPECT_VAR}=1 to update the expectation at {}:{}",
+		mismatch.render(color_enabled()),
+		caller.file(),
+		caller.line()
+	);
+}
+
+fn update_requested() -> bool {
+	std::env::var_os(UPDATE_EXPECT_VAR).is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Render `actual` so that parsing it with `default_path` gives it back exactly.
+///
+/// The result always ends with a newline, so the literal's closing line stays
+/// separate; a file without a final newline gets a header with `no-eol`.
+fn render_expectation(actual: &Fixture, default_path: &str) -> String {
+	match actual.files.as_slice() {
+		[single] if single.text.ends_with('\n') && Fixture::parse_with_default_path(&single.text, default_path) == *actual => single.text.clone(),
+		_ => actual.render_lossless(),
+	}
+}
+
+/// Where `line` of the compiled source is after the rewrites in `shifts`.
+fn current_line(shifts: &[LineShift], line: usize) -> usize {
+	shifts
+		.iter()
+		.fold(line, |line, shift| if line > shift.last_changed { line.saturating_add_signed(shift.delta) } else { line })
+}
+
+/// How rewriting `old` into `new` moved the lines below the rewrite.
+fn line_shift(old: &str, new: &str) -> LineShift {
+	let unchanged_tail = old.lines().rev().zip(new.lines().rev()).take_while(|(a, b)| a == b).count();
+	let old_lines = old.lines().count();
+	LineShift {
+		last_changed: old_lines - unchanged_tail,
+		delta: new.lines().count() as isize - old_lines as isize,
+	}
+}
+
+/// `Location::file` is relative to where rustc ran (the workspace root), while tests
+/// run in the package directory. Try the cwd and its ancestors.
+fn resolve_source_path(file: &str) -> PathBuf {
+	let file = Path::new(file);
+	if file.is_absolute() {
+		return file.to_path_buf();
+	}
+	let cwd = std::env::current_dir().expect("failed to get current dir");
+	cwd.ancestors().map(|dir| dir.join(file)).find(|p| p.exists()).unwrap_or_else(|| cwd.join(file))
+}
+
+/// Replace the half after the `separator` line of the string literal containing `fixture` in `source`.
+///
+/// If `fixture` occurs several times, the occurrence closest above `caller_line` wins.
+fn rewrite_after_half(source: &str, fixture: &str, caller_line: usize, rendered: &str, separator: &str) -> Result<String, String> {
+	if fixture.is_empty() {
+		return Err("fixture is empty".to_owned());
+	}
+	let line_of = |idx: usize| source[..idx].matches('\n').count() + 1;
+	let occurrences: Vec<usize> = source.match_indices(fixture).map(|(idx, _)| idx).collect();
+	let start = occurrences
+		.iter()
+		.copied()
+		.rfind(|&idx| line_of(idx) <= caller_line)
+		.or_else(|| occurrences.first().copied())
+		.ok_or("fixture literal not found verbatim in source (only raw strings or strings without escapes can be updated)")?;
+	let end = start + fixture.len();
+
+	// Opening delimiter: `"`, or `r`, some `#`s and `"`
+	let before = source[..start].strip_suffix('"').ok_or("fixture is not a whole string literal")?;
+	let hashes = before.len() - before.trim_end_matches('#').len();
+	let raw = before[..before.len() - hashes].ends_with('r');
+	if !raw && hashes > 0 {
+		return Err("fixture is not a whole string literal".to_owned());
+	}
+	let opening_start = start - 1 - hashes - usize::from(raw);
+	let closing = format!("\"{}", "#".repeat(hashes));
+	if !source[end..].starts_with(&closing) {
+		return Err("fixture is not a whole string literal".to_owned());
+	}
+
+	let content = replace_after_half(fixture, rendered, separator)?;
+
+	// Pick delimiters that can hold the new content
+	let needs_raw = raw || content.contains('"') || content.contains('\\');
+	let literal = if needs_raw {
+		let hashes = hashes.max(required_hashes(&content));
+		let hashes = "#".repeat(hashes);
+		format!("r{hashes}\"{content}\"{hashes}")
+	} else {
+		format!("\"{content}\"")
+	};
+
+	Ok(format!("{}{literal}{}", &source[..opening_start], &source[end + closing.len()..]))
+}
+
+/// Replace everything between the `separator` line and the literal's closing line.
+///
+/// Like [`BeforeAfter::parse`], the separator is a whole line once the
+/// literal's common indentation is removed.
+fn replace_after_half(content: &str, rendered: &str, separator: &str) -> Result<String, String> {
+	let indent_len = content
+		.lines()
+		.filter(|line| !line.trim().is_empty())
+		.map(|line| line.len() - line.trim_start().len())
+		.min()
+		.unwrap_or(0);
+	let mut offset = 0;
+	let mut found = None;
+	for line in content.split_inclusive('\n') {
+		let text = line.strip_suffix('\n').unwrap_or(line);
+		if text.get(indent_len..) == Some(separator) {
+			if found.is_some() {
+				return Err(format!("more than one `{separator}` separator"));
+			}
+			found = Some((offset, line));
+		}
+		offset += line.len();
+	}
+	let (sep_offset, sep_line) = found.ok_or_else(|| format!("no `{separator}` separator"))?;
+	let indent = &sep_line[..indent_len];
+	let after_start = sep_offset + sep_line.len();
+
+	// Keep the whitespace before the closing quote, so it stays on its own line
+	let after = &content[after_start..];
+	let tail = match after.rsplit_once('\n') {
+		Some((_, tail)) if tail.trim().is_empty() => tail,
+		None if after.trim().is_empty() => after,
+		_ => "",
+	};
+
+	let mut out = content[..after_start].to_owned();
+	for line in rendered.split_inclusive('\n') {
+		if !line.trim().is_empty() {
+			out.push_str(indent);
+		}
+		out.push_str(line);
+	}
+	out.push_str(tail);
+	Ok(out)
+}
+
+/// Minimum number of `#`s for a raw string holding `content`.
+fn required_hashes(content: &str) -> usize {
+	content
+		.match_indices('"')
+		.map(|(idx, _)| content[idx + 1..].len() - content[idx + 1..].trim_start_matches('#').len() + 1)
+		.max()
+		.unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const FIXTURE: &str = "\n            //- /a.txt\n            one\n            =>\n            //- /a.txt\n            uno\n        ";
+
+	fn source_with(literal_prefix: &str, literal_suffix: &str) -> String {
+		format!("fn t() {{\n    let f = {literal_prefix}{FIXTURE}{literal_suffix};\n    check(f);\n}}\n")
+	}
+
+	#[test]
+	fn test_rewrite_keeps_indentation() {
+		let source = source_with("r#\"", "\"#");
+		let updated = rewrite_after_half(&source, FIXTURE, 3, "//- /a.txt\nONE\n\ntwo\n", "=>").unwrap();
+		assert_eq!(
+			updated,
+			"fn t() {\n    let f = r#\"\n            //- /a.txt\n            one\n            =>\n            //- /a.txt\n            ONE\n\n            two\n        \"#;\n    check(f);\n}\n"
+		);
+	}
+
+	#[test]
+	fn test_rewrite_adds_hashes_when_needed() {
+		let source = source_with("r#\"", "\"#");
+		let updated = rewrite_after_half(&source, FIXTURE, 3, "//- /a.txt\nlet s = \"#x\";\n", "=>").unwrap();
+		assert!(updated.contains("let f = r##\"\n"));
+		assert!(updated.contains("            let s = \"#x\";\n        \"##;"));
+
+		let source = source_with("\"", "\"");
+		let updated = rewrite_after_half(&source, FIXTURE, 3, "//- /a.txt\n\"quoted\"\n", "=>").unwrap();
+		assert!(updated.contains("let f = r#\"\n"));
+		assert!(updated.contains("\"quoted\"\n        \"#;"));
+	}
+
+	#[test]
+	fn test_rewrite_picks_occurrence_above_caller() {
+		let source = format!("{}{}", source_with("r#\"", "\"#"), source_with("r#\"", "\"#"));
+		let updated = rewrite_after_half(&source, FIXTURE, 13, "//- /a.txt\nsecond\n", "=>").unwrap();
+		let first_end = updated.find("check(f)").unwrap();
+		assert!(updated[..first_end].contains("uno"));
+		assert!(updated[first_end..].contains("second"));
+	}
+
+	#[test]
+	fn test_rewrite_rejects_unknown_literal() {
+		assert!(rewrite_after_half("fn t() {}", FIXTURE, 1, "x\n", "=>").is_err());
+	}
+
+	#[test]
+	fn test_rewrite_custom_separator() {
+		let fixture = "\n        a\n        =>\n        --\n        b\n    ";
+		let source = format!("let f = r#\"{fixture}\"#;\n");
+		let rendered = render_expectation(&Fixture::parse_with_default_path("c\n", "/x.txt"), "/x.txt");
+		let updated = rewrite_after_half(&source, fixture, 1, &rendered, "--").unwrap();
+		assert_eq!(updated, "let f = r#\"\n        a\n        =>\n        --\n        c\n    \"#;\n");
+	}
+
+	#[test]
+	fn test_rewritten_expectation_parses_back() {
+		let fixture = "\n        a\n        =>\n        b\n    ";
+		let source = format!("let f = r#\"{fixture}\"#;\n");
+		let header_like = Fixture {
+			files: vec![crate::FixtureFile::new("/main.rs".to_owned(), "//- /not/a/header\n".to_owned())],
+		};
+		for actual in [Fixture::parse("no newline"), Fixture::parse("//- /a.txt\none\n//- /b.txt\ntwo"), header_like] {
+			let updated = rewrite_after_half(&source, fixture, 1, &render_expectation(&actual, "/main.rs"), "=>").unwrap();
+			let literal = updated.strip_prefix("let f = r#\"").unwrap().strip_suffix("\"#;\n").unwrap();
+			assert_eq!(crate::parse_before_after(literal).1, actual, "{literal:?}");
+		}
+	}
+
+	#[test]
+	fn test_line_shifts_follow_earlier_rewrites() {
+		let old = "a\nb\nc\nd\n";
+		let shifts = [line_shift(old, "a\nB\nB\nB\nc\nd\n"), line_shift("x\ny\n", "x\n")];
+		assert_eq!(current_line(&shifts[..1], 1), 1);
+		assert_eq!(current_line(&shifts[..1], 2), 2);
+		assert_eq!(current_line(&shifts[..1], 4), 6);
+		assert_eq!(current_line(&shifts, 4), 5);
+	}
+
+	#[test]
+	fn test_rewrite_ignores_indented_separator_content() {
+		let fixture = "\n        match x {\n            =>\n        }\n        =>\n        old\n    ";
+		let source = format!("let f = r#\"{fixture}\"#;\n");
+		let updated = rewrite_after_half(&source, fixture, 1, "new\n", "=>").unwrap();
+		assert_eq!(updated, "let f = r#\"\n        match x {\n            =>\n        }\n        =>\n        new\n    \"#;\n");
+	}
+
+	#[test]
+	fn test_assert_after_eq_passes() {
+		let (before, _) = crate::parse_before_after(FIXTURE);
+		let mut actual = before.clone();
+		actual.files[0].text = "uno\n".to_owned();
+		assert_after_eq(FIXTURE, &actual);
+	}
+
+	#[test]
+	// With UPDATE_EXPECT set this fails to find the (escaped) literal instead, which also panics
+	#[should_panic(expected = "expectation at src/expect.rs")]
+	fn test_assert_after_eq_fails() {
+		let (before, _) = crate::parse_before_after(FIXTURE);
+		assert_after_eq(FIXTURE, &before);
+	}
+}
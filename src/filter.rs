@@ -0,0 +1,127 @@
+//! Gitignore-style glob filters over fixture paths.
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+/// Include and exclude lists of gitignore-style globs, matched against fixture paths.
+///
+/// - `*` and `?` don't cross `/`; `**` matches any number of directories
+/// - a glob without a `/` (other than a trailing one) matches at any depth,
+///   e.g. `*.rs` matches `/src/main.rs`
+/// - a glob with a `/` is anchored at the fixture root, e.g. `/src/*.rs` or `src/*.rs`
+/// - a glob matching a directory matches everything below it, e.g. `target/`
+///
+/// A path passes if it matches some include (or there are none) and no exclude.
+/// Used by [`FixtureRenderer::filter`](crate::FixtureRenderer::filter) and
+/// [`ReadOptions::filter`](crate::ReadOptions::filter), so capture and render can share one definition.
+///
+/// # Example
+///
+/// ```
+/// use v_fixtures::PathFilter;
+///
+/// let filter = PathFilter::new().include("src/**/*.rs").exclude("src/generated/");
+/// assert!(filter.matches("/src/main.rs"));
+/// assert!(filter.matches("/src/cli/args.rs"));
+/// assert!(!filter.matches("/src/generated/schema.rs"));
+/// assert!(!filter.matches("/tests/it.rs"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct PathFilter {
+	includes: Vec<String>,
+	excludes: Vec<String>,
+	include_set: GlobSet,
+	exclude_set: GlobSet,
+}
+
+impl Default for PathFilter {
+	fn default() -> Self {
+		Self {
+			includes: Vec::new(),
+			excludes: Vec::new(),
+			include_set: GlobSet::empty(),
+			exclude_set: GlobSet::empty(),
+		}
+	}
+}
+
+impl PathFilter {
+	/// A filter that passes every path.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Build a filter from globs, where a leading `!` makes a glob an exclude.
+	pub fn from_globs<'a>(globs: impl IntoIterator<Item = &'a str>) -> Self {
+		globs.into_iter().fold(Self::new(), |filter, glob| match glob.strip_prefix('!') {
+			Some(glob) => filter.exclude(glob),
+			None => filter.include(glob),
+		})
+	}
+
+	/// Only pass paths matching `glob` (or another include).
+	pub fn include(mut self, glob: &str) -> Self {
+		self.includes.push(glob.to_owned());
+		self.include_set = build_set(&self.includes);
+		self
+	}
+
+	/// Reject paths matching `glob`.
+	pub fn exclude(mut self, glob: &str) -> Self {
+		self.excludes.push(glob.to_owned());
+		self.exclude_set = build_set(&self.excludes);
+		self
+	}
+
+	/// Whether the fixture path `path` (e.g. `/src/main.rs`) passes the filter.
+	pub fn matches(&self, path: &str) -> bool {
+		let path = path.trim_start_matches('/');
+		(self.includes.is_empty() || self.include_set.is_match(path)) && !self.exclude_set.is_match(path)
+	}
+}
+
+/// Compile gitignore-style globs, matching paths relative to the fixture root.
+fn build_set(globs: &[String]) -> GlobSet {
+	let mut builder = GlobSetBuilder::new();
+	for glob in globs {
+		let trimmed = glob.trim_end_matches('/');
+		let anchored = trimmed.contains('/');
+		let trimmed = trimmed.trim_start_matches('/');
+		let base = if anchored || trimmed.starts_with("**") {
+			trimmed.to_owned()
+		} else {
+			format!("**/{trimmed}")
+		};
+		// The glob itself, and everything below what it matches
+		for pattern in [base.clone(), format!("{base}/**")] {
+			let glob = GlobBuilder::new(&pattern)
+				.literal_separator(true)
+				.build()
+				.unwrap_or_else(|e| panic!("invalid glob {glob:?}: {e}"));
+			builder.add(glob);
+		}
+	}
+	builder.build().expect("failed to build glob set")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_gitignore_style_globs() {
+		let filter = PathFilter::from_globs(["*.rs", "!/src/generated/**", "!target/"]);
+		assert!(filter.matches("/main.rs"));
+		assert!(filter.matches("/src/deep/mod.rs"));
+		assert!(!filter.matches("/src/generated/a.rs"));
+		assert!(!filter.matches("/target/debug/build.rs"));
+		assert!(!filter.matches("/README.md"));
+
+		// `*` doesn't cross directories, anchored globs start at the root
+		let filter = PathFilter::new().include("/src/*.rs");
+		assert!(filter.matches("/src/lib.rs"));
+		assert!(!filter.matches("/src/a/lib.rs"));
+		assert!(!filter.matches("/x/src/lib.rs"));
+
+		assert!(PathFilter::new().matches("/anything"));
+	}
+}
@@ -0,0 +1,159 @@
+//! Comparison against golden directories, with a bless mode.
+//!
+//! Expected outputs can live as real directory trees (e.g. `tests/golden/<name>/`),
+//! browsable as ordinary files. With `V_FIXTURES_BLESS=1` set, a failing
+//! comparison overwrites the golden directory with the actual state instead,
+//! deleting stale files.
+
+use std::{fs, path::Path};
+
+use crate::{Fixture, FixtureMismatch, TempFixture, compare::color_enabled, walk_without_git};
+
+/// Environment variable that makes golden-directory assertions overwrite the golden directory.
+pub const BLESS_VAR: &str = "V_FIXTURES_BLESS";
+
+impl Fixture {
+	/// Assert that the fixture matches the golden directory at `golden`.
+	///
+	/// The golden directory is read with [`Fixture::read_from_directory`]; a missing
+	/// directory counts as empty. With `V_FIXTURES_BLESS=1`, a mismatch overwrites the
+	/// golden directory with this fixture (deleting files it doesn't have) instead of panicking.
+	#[track_caller]
+	pub fn assert_matches_dir(&self, golden: impl AsRef<Path>) {
+		let golden = golden.as_ref();
+		let expected = Fixture::read_from_directory(golden).unwrap_or_default();
+		let mismatch = FixtureMismatch::compare(&expected, self);
+		if mismatch.is_empty() {
+			return;
+		}
+
+		if bless_requested() {
+			bless(golden, self);
+			eprintln!("blessed golden directory {}", golden.display());
+			return;
+		}
+
+		panic!(
+			"golden directory {} does not match\n\n{}\nset {BLESS_VAR}=1 to overwrite it with the actual state",
+			golden.display(),
+			mismatch.render(color_enabled())
+		);
+	}
+}
+
+impl TempFixture {
+	/// Assert that the current disk state matches the golden directory at `golden`.
+	///
+	/// Compares [`TempFixture::read_all_from_disk`] using [`Fixture::assert_matches_dir`].
+	///
+	/// # Example
+	///
+	/// ```ignore
+	/// let temp = fixture.write_to_tempdir();
+	/// // ... run your tool ...
+	/// temp.assert_matches_dir("tests/golden/format_all");
+	/// ```
+	#[track_caller]
+	pub fn assert_matches_dir(&self, golden: impl AsRef<Path>) {
+		self.read_all_from_disk().assert_matches_dir(golden);
+	}
+}
+
+fn bless_requested() -> bool {
+	std::env::var_os(BLESS_VAR).is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Make `golden` contain exactly the files of `actual`.
+fn bless(golden: &Path, actual: &Fixture) {
+	if golden.exists() {
+		for entry in walk_without_git(walkdir::WalkDir::new(golden)) {
+			let path = entry.path();
+			if !entry.file_type().is_dir() {
+				let relative = format!("/{}", path.strip_prefix(golden).expect("path should be under golden dir").to_string_lossy());
+				if !actual.contains(&relative) {
+					fs::remove_file(path).expect("failed to remove stale golden file");
+				}
+			}
+		}
+	}
+	actual.write_to_directory(golden);
+
+	// Drop directories emptied by the removals (children come before parents)
+	for entry in walk_without_git(walkdir::WalkDir::new(golden).min_depth(1).contents_first(true)) {
+		if entry.file_type().is_dir() {
+			let _ = fs::remove_dir(entry.path());
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_matches_golden_dir() {
+		let fixture = Fixture::parse("//- /out/a.txt\na\n//- /b.txt\nb\n");
+		let golden = fixture.write_to_tempdir();
+		let temp = fixture.write_to_tempdir();
+		temp.assert_matches_dir(&golden.root);
+	}
+
+	#[test]
+	#[should_panic(expected = "fixtures differ: 1 missing, 0 extra, 1 differing")]
+	fn test_golden_mismatch_reports() {
+		let golden = Fixture::parse("//- /a.txt\na\n//- /b.txt\nb\n").write_to_tempdir();
+		let actual = Fixture::parse("//- /a.txt\nchanged\n");
+		actual.assert_matches_dir(&golden.root);
+	}
+
+	#[test]
+	fn test_bless_replaces_and_prunes() {
+		let golden = Fixture::parse("//- /stale/old.txt\nold\n//- /keep.txt\nbefore\n").write_to_tempdir();
+		// Sorted by path, as read back from disk
+		let actual = Fixture::parse("//- /keep.txt\nafter\n//- /new/file.txt\nnew\n");
+
+		bless(&golden.root, &actual);
+
+		assert_eq!(Fixture::read_from_directory(&golden.root).unwrap(), actual);
+		assert!(!golden.path("/stale").exists());
+	}
+
+	#[test]
+	fn test_bless_leaves_git_alone() {
+		let golden = Fixture::parse("//- /.git/HEAD\nref: refs/heads/main\n//- /a.txt\nold\n").write_to_tempdir();
+		bless(&golden.root, &Fixture::parse("//- /a.txt\nnew\n"));
+		assert_eq!(golden.read("/.git/HEAD"), "ref: refs/heads/main\n");
+		assert_eq!(golden.read("/a.txt"), "new\n");
+	}
+
+	#[test]
+	fn test_git_named_files_are_compared() {
+		let fixture = Fixture::parse(
+			"//- /.gitignore
+target
+//- /site.github.io/index.html
+hi
+",
+		);
+		let golden = fixture.write_to_tempdir();
+		fixture.write_to_tempdir().assert_matches_dir(&golden.root);
+
+		let stale = Fixture::parse(
+			"//- /.github/ci.yml
+old
+//- /a.txt
+old
+",
+		)
+		.write_to_tempdir();
+		bless(
+			&stale.root,
+			&Fixture::parse(
+				"//- /a.txt
+new
+",
+			),
+		);
+		assert!(!stale.path("/.github").exists());
+	}
+}
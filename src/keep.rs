@@ -0,0 +1,139 @@
+//! Keeping the temp directory of a failed test for inspection.
+
+use std::path::{Path, PathBuf};
+
+use crate::{Fixture, ReadOptions, TempFixture, tree::render_tree};
+
+/// Environment variable that keeps the directory of every [`TempFixture`] dropped by a failing test.
+pub const KEEP_ON_FAILURE_VAR: &str = "V_FIXTURES_KEEP";
+
+impl TempFixture {
+	/// Keep the temp directory if the test panics, like setting `V_FIXTURES_KEEP=1`.
+	///
+	/// On drop during a panic, the directory is moved next to itself with a
+	/// `_kept` suffix (`_kept2`, `_kept3`, ... if an earlier run left one behind),
+	/// out of the way of the cleanup, and its new path is printed to stderr along
+	/// with a tree of what it contains.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::Fixture;
+	///
+	/// let temp = Fixture::parse("//- /config.toml\nlevel = 1\n").write_to_tempdir().keep_on_failure();
+	/// // ... run the tool under test; if an assertion fails, the directory survives ...
+	/// ```
+	pub fn keep_on_failure(mut self) -> Self {
+		self.keep.requested = true;
+		self
+	}
+}
+
+/// Saves a [`TempFixture`]'s directory from cleanup when dropped during a panic.
+///
+/// A separate field rather than `Drop` on [`TempFixture`] itself, so its public
+/// fields can still be moved out. It's declared before `temp_dir` so it runs first.
+#[derive(Debug)]
+pub(crate) struct KeepGuard {
+	root: PathBuf,
+	requested: bool,
+}
+
+impl KeepGuard {
+	pub(crate) fn new(root: &Path) -> Self {
+		Self {
+			root: root.to_path_buf(),
+			requested: false,
+		}
+	}
+}
+
+impl Drop for KeepGuard {
+	fn drop(&mut self) {
+		if !std::thread::panicking() || !(self.requested || keep_requested()) {
+			return;
+		}
+		let Some(kept) = (1..).map(|attempt| kept_path(&self.root, attempt)).find(|kept| kept.symlink_metadata().is_err()) else {
+			return;
+		};
+		if let Err(e) = std::fs::rename(&self.root, &kept) {
+			eprintln!("test failed, could not keep fixture directory {}: {e}", self.root.display());
+			return;
+		}
+		let disk = Fixture::read_from_directory_with(&kept, &ReadOptions::new().record_links_and_modes()).unwrap_or_default();
+		let tree = render_tree(disk.files.iter().map(|f| (f, f.text.len())));
+		eprint!("test failed, kept fixture directory {}\n{tree}", kept.display());
+	}
+}
+
+/// Where the directory at `root` is moved to when kept, on the `attempt`th free name.
+fn kept_path(root: &Path, attempt: usize) -> PathBuf {
+	let mut name = root.file_name().unwrap_or_default().to_os_string();
+	name.push("_kept");
+	if attempt > 1 {
+		name.push(attempt.to_string());
+	}
+	root.with_file_name(name)
+}
+
+fn keep_requested() -> bool {
+	std::env::var_os(KEEP_ON_FAILURE_VAR).is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{
+		fs,
+		path::{Path, PathBuf},
+		sync::mpsc,
+		thread,
+	};
+
+	use super::kept_path;
+	use crate::Fixture;
+
+	/// Root of a fixture dropped by a panicking thread, after `before_panic` ran on it.
+	fn root_after_panic(keep: bool, before_panic: fn(&Path)) -> PathBuf {
+		let (sender, receiver) = mpsc::channel();
+		let result = thread::spawn(move || {
+			let temp = Fixture::parse("//- /src/lib.rs\npub fn lib() {}\n").write_to_tempdir();
+			let temp = if keep { temp.keep_on_failure() } else { temp };
+			before_panic(&temp.root);
+			sender.send(temp.root.clone()).unwrap();
+			panic!("test failure");
+		})
+		.join();
+		assert!(result.is_err());
+		receiver.recv().unwrap()
+	}
+
+	#[test]
+	fn test_keep_on_failure() {
+		let root = kept_path(&root_after_panic(true, |_| {}), 1);
+		assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), "pub fn lib() {}\n");
+		fs::remove_dir_all(root).unwrap();
+
+		// Without a policy the directory goes away as usual
+		if std::env::var_os(super::KEEP_ON_FAILURE_VAR).is_none() {
+			assert!(!root_after_panic(false, |_| {}).exists());
+		}
+	}
+
+	#[test]
+	fn test_keep_next_to_earlier_kept_directory() {
+		let root = root_after_panic(true, |root| fs::create_dir_all(kept_path(root, 1).join("earlier")).unwrap());
+		assert!(kept_path(&root, 1).join("earlier").exists());
+		assert!(kept_path(&root, 2).join("src/lib.rs").exists());
+		fs::remove_dir_all(kept_path(&root, 1)).unwrap();
+		fs::remove_dir_all(kept_path(&root, 2)).unwrap();
+	}
+
+	#[test]
+	fn test_fields_can_be_moved_out() {
+		let temp = Fixture::parse("//- /a.txt\na\n").write_to_tempdir().keep_on_failure();
+		let files = temp.files;
+		let temp_dir = temp.temp_dir;
+		assert_eq!(files.len(), 1);
+		assert!(temp_dir.path().join("a.txt").exists());
+	}
+}
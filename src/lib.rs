@@ -60,16 +60,420 @@
 //! let result = temp.read_all_from_disk();
 //! insta::assert_snapshot!(result.render(), @"...");
 //! ```
-
-use std::{borrow::Cow, fs, path::PathBuf};
+//!
+//! With the `insta` feature, `assert_fixture_snapshot!(&temp)` does the same in one
+//! step, naming the snapshot after the test and recording the file list as metadata.
+//!
+//! # Temp paths
+//!
+//! `{{ROOT}}` (and `{{CWD}}`, see [`TempDirOptions::cwd`]) in file contents expand to
+//! the temp dir's absolute path when writing with [`Fixture::write_to_tempdir`], and
+//! captures like [`TempFixture::read_all_from_disk`] turn the absolute paths back into
+//! placeholders, so fixtures can mention absolute paths while snapshots stay stable.
+//! [`TempFixture::read`] returns contents as on disk, and
+//! [`TempDirOptions::without_placeholders`] writes the placeholders literally.
+//!
+//! # Compressed files
+//!
+//! With the `gzip`/`zstd` features, a `compress:` attribute on the meta line
+//! stores the file compressed on disk, while the fixture text stays readable:
+//!
+//! ```ignore
+//! use v_fixtures::Fixture;
+//!
+//! let fixture = Fixture::parse(r#"
+//!     //- /logs/app.log.gz compress:gzip
+//!     started
+//! "#);
+//! let temp = fixture.write_to_tempdir();
+//! // On disk the file is gzip; reading it back decompresses it again.
+//! assert_eq!(temp.read("/logs/app.log.gz"), "started\n");
+//! ```
+//!
+//! # Structured files
+//!
+//! With the `json`/`toml`/`yaml` features, files with those extensions are compared
+//! by value, so key order and formatting don't matter. A `compare:` attribute picks
+//! the mode explicitly (`compare:text` for exact comparison), and
+//! [`FixtureRenderer::canonicalize_structured`] sorts keys and pretty-prints them
+//! for stable snapshots.
+
+use std::{
+	borrow::Cow,
+	fs,
+	path::{Path, PathBuf},
+};
+
+use limit::Limits;
+use redact::PresetNumbering;
+
+mod before_after;
+mod compare;
+mod compression;
+mod diff;
+mod expect;
+mod filter;
+mod golden;
+mod keep;
+mod limit;
+mod markdown;
+mod matching;
+mod patch;
+mod redact;
+mod structured;
+mod temp;
+mod terminal;
+mod tree;
+pub use before_after::BeforeAfter;
+pub use compare::{CompareOptions, DifferenceKind, DifferingFile, FixtureMismatch, assert_fixture_eq, assert_fixture_eq_with};
+pub use compression::Compression;
+pub use diff::{DiffHunk, DiffLine, FileChange, FixtureDiff, LineTag};
+pub use expect::{UPDATE_EXPECT_VAR, assert_after_eq};
+pub use filter::PathFilter;
+pub use golden::BLESS_VAR;
+pub use keep::KEEP_ON_FAILURE_VAR;
+pub use limit::{ReadOptions, SizeLimit};
+pub use matching::{PatternMismatch, assert_fixture_matches};
+pub use patch::PatchError;
+pub use redact::RedactionPreset;
+pub use structured::CompareMode;
+pub use temp::{TEMP_DIR_VAR, TempDirOptions};
 
 /// A single file in a fixture
-#[derive(Clone, Debug, Eq, PartialEq)]
+///
+/// Since 0.4 a file also carries its [`FileMeta`]. Build files with
+/// [`FixtureFile::new`] or a struct literal ending in `..Default::default()`,
+/// so that further attributes don't break your code.
+#[derive(Clone, Debug, Default, Eq, PartialEq, derive_new::new)]
 pub struct FixtureFile {
 	/// Path relative to fixture root (e.g., "/main.rs" or "/tests/test.rs")
 	pub path: String,
 	/// File contents with meta lines stripped
 	pub text: String,
+	/// Attributes from the `//-` meta line
+	#[new(default)]
+	pub meta: FileMeta,
+}
+
+/// Per-file attributes given after the path on a `//-` meta line.
+///
+/// Attributes are `key:value` tokens or bare flags, e.g. `//- /app.log.gz compress:gzip`
+/// or `//- /debug.log optional`. Unknown tokens are ignored.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FileMeta {
+	/// Store the file compressed on disk. `text` is always the decompressed content.
+	pub compress: Option<Compression>,
+	/// `compare:json|toml|yaml|text`: how the file is compared, overriding its extension.
+	pub compare: Option<CompareMode>,
+	/// `mode:755`: Unix permission bits, set on write. Captured for executable files
+	/// with [`ReadOptions::record_links_and_modes`].
+	pub mode: Option<u32>,
+	/// `symlink:target`: the file is a symbolic link to `target` (Unix only); `text` is empty.
+	/// A target with whitespace is written quoted, like `symlink:"My Documents"`.
+	pub symlink: Option<String>,
+	/// `optional`: when used as an expected pattern, the file may be absent.
+	pub optional: bool,
+	/// `ignore-content`: when used as an expected pattern, only the file's existence is checked.
+	pub ignore_content: bool,
+}
+
+impl FileMeta {
+	/// Parse meta tokens following the path.
+	fn parse<'a>(tokens: impl Iterator<Item = &'a str>) -> Self {
+		let mut meta = Self::default();
+		for token in tokens {
+			match token.split_once(':') {
+				Some(("compress", value)) => {
+					let compress = Compression::from_name(value).unwrap_or_else(|| panic!("unknown compression in fixture meta: {value:?}"));
+					assert!(compress.enabled(), "`compress:{value}` requires the `{}` feature of v_fixtures", compress.name());
+					meta.compress = Some(compress);
+				}
+				Some(("compare", value)) => {
+					let mode = CompareMode::from_name(value).unwrap_or_else(|| panic!("unknown compare mode in fixture meta: {value:?}"));
+					assert!(mode.enabled(), "`compare:{value}` requires the `{}` feature of v_fixtures", mode.name());
+					meta.compare = Some(mode);
+				}
+				Some(("mode", value)) => {
+					meta.mode = Some(u32::from_str_radix(value, 8).unwrap_or_else(|_| panic!("invalid octal mode in fixture meta: {value:?}")));
+				}
+				Some(("symlink", value)) => meta.symlink = Some(value.to_owned()),
+				None if token == "optional" => meta.optional = true,
+				None if token == "ignore-content" => meta.ignore_content = true,
+				_ => {}
+			}
+		}
+		meta
+	}
+
+	/// Render as meta tokens, each preceded by a space (empty if no attributes are set).
+	fn render(&self) -> String {
+		let mut out = String::new();
+		if let Some(compress) = self.compress {
+			out.push_str(" compress:");
+			out.push_str(compress.name());
+		}
+		if let Some(compare) = self.compare {
+			out.push_str(" compare:");
+			out.push_str(compare.name());
+		}
+		if let Some(mode) = self.mode {
+			out.push_str(&format!(" mode:{mode:o}"));
+		}
+		if let Some(target) = &self.symlink {
+			out.push_str(" symlink:");
+			out.push_str(&quote_value(target));
+		}
+		if self.optional {
+			out.push_str(" optional");
+		}
+		if self.ignore_content {
+			out.push_str(" ignore-content");
+		}
+		out
+	}
+}
+
+/// Placeholder for the temp root's absolute path, expanded when writing to a temp dir.
+pub const ROOT_PLACEHOLDER: &str = "{{ROOT}}";
+/// Placeholder for the temp fixture's working directory (see [`TempFixture::cwd`]).
+pub const CWD_PLACEHOLDER: &str = "{{CWD}}";
+
+/// Replace `{{ROOT}}` and `{{CWD}}` with the given absolute paths.
+fn expand_placeholders<'t>(text: &'t str, root: &Path, cwd: &Path) -> Cow<'t, str> {
+	if !text.contains("{{") {
+		return Cow::Borrowed(text);
+	}
+	Cow::Owned(text.replace(ROOT_PLACEHOLDER, &root.to_string_lossy()).replace(CWD_PLACEHOLDER, &cwd.to_string_lossy()))
+}
+
+/// Replace occurrences of absolute paths with their placeholders, the reverse of [`expand_placeholders`].
+///
+/// Each path is also matched in canonical form (e.g. `/private/var/...` on macOS),
+/// and only where it isn't the start of a longer name (`<root>/app` in `<root>/app.log`).
+fn collapse_placeholders(mut text: String, paths: &[(&Path, &str)]) -> String {
+	for &(path, placeholder) in paths {
+		let canonical = path.canonicalize().ok().filter(|c| c != path);
+		for path in canonical.as_deref().into_iter().chain([path]) {
+			let path = path.to_string_lossy();
+			if text.contains(path.as_ref()) {
+				text = replace_whole_path(&text, &path, placeholder);
+			}
+		}
+	}
+	text
+}
+
+fn replace_whole_path(text: &str, path: &str, placeholder: &str) -> String {
+	let mut out = String::with_capacity(text.len());
+	let mut rest = text;
+	while let Some(idx) = rest.find(path) {
+		let after = &rest[idx + path.len()..];
+		let continues_name = after.starts_with(|c: char| c.is_alphanumeric() || matches!(c, '.' | '_' | '-'));
+		out.push_str(&rest[..idx]);
+		out.push_str(if continues_name { path } else { placeholder });
+		rest = after;
+	}
+	out.push_str(rest);
+	out
+}
+
+/// Read a file as text, transparently decompressing compressed content.
+///
+/// Returns `None` for unreadable or non-UTF-8 files.
+fn read_text_file(path: &Path) -> Option<(String, FileMeta)> {
+	let bytes = fs::read(path).ok()?;
+	if let Some(compress) = Compression::detect(&bytes)
+		&& let Some(text) = compress.decompress(&bytes)
+	{
+		return Some((
+			text,
+			FileMeta {
+				compress: Some(compress),
+				..Default::default()
+			},
+		));
+	}
+	String::from_utf8(bytes).ok().map(|text| (text, FileMeta::default()))
+}
+
+/// Capture a file like [`read_text_file`] and apply `limit`.
+///
+/// With `links_and_modes`, symlinks are recorded as such (without following
+/// them), and so is the mode of executable files.
+fn capture_file(path: &Path, links_and_modes: bool, limit: Option<SizeLimit>) -> Option<(String, FileMeta)> {
+	if links_and_modes && fs::symlink_metadata(path).ok()?.file_type().is_symlink() {
+		let target = fs::read_link(path).ok()?;
+		let meta = FileMeta {
+			symlink: Some(target.to_string_lossy().into_owned()),
+			..Default::default()
+		};
+		return Some((String::new(), meta));
+	}
+	let (text, mut meta) = match limit.and_then(|limit| limit.read_partial(path)) {
+		Some(text) => (text, FileMeta::default()),
+		None => {
+			let (text, meta) = read_text_file(path)?;
+			match limit {
+				Some(limit) => (limit.apply(&text).into_owned(), meta),
+				None => (text, meta),
+			}
+		}
+	};
+	#[cfg(unix)]
+	if links_and_modes {
+		use std::os::unix::fs::PermissionsExt as _;
+		let mode = fs::metadata(path).ok()?.permissions().mode() & 0o7777;
+		if mode & 0o111 != 0 {
+			meta.mode = Some(mode);
+		}
+	}
+	Some((text, meta))
+}
+
+/// Whether a directory walk captures `entry`: files, including those behind
+/// symlinks, or with `links_and_modes` the symlinks themselves.
+fn is_captured(entry: &walkdir::DirEntry, links_and_modes: bool) -> bool {
+	if links_and_modes {
+		entry.file_type().is_file() || entry.path_is_symlink()
+	} else {
+		entry.path().is_file()
+	}
+}
+
+/// Write a fixture file to `path` as described by its meta.
+fn write_file(path: &Path, file: &FixtureFile) {
+	if let Some(target) = &file.meta.symlink {
+		#[cfg(unix)]
+		{
+			if fs::symlink_metadata(path).is_ok() {
+				fs::remove_file(path).expect("failed to replace file with symlink");
+			}
+			std::os::unix::fs::symlink(target, path).expect("failed to create symlink");
+			return;
+		}
+		#[cfg(not(unix))]
+		panic!("symlink fixture files are only supported on Unix: {} -> {target}", file.path);
+	}
+	match file.meta.compress {
+		Some(compress) => fs::write(path, compress.compress(&file.text)),
+		None => fs::write(path, &file.text),
+	}
+	.expect("failed to write fixture file");
+	#[cfg(unix)]
+	if let Some(mode) = file.meta.mode {
+		use std::os::unix::fs::PermissionsExt as _;
+		fs::set_permissions(path, fs::Permissions::from_mode(mode)).expect("failed to set fixture file mode");
+	}
+}
+
+/// Split what follows `//-` into the path and meta tokens, or `None` if there is no path.
+///
+/// The path, and the value of a `key:value` token, may be written in double quotes,
+/// with `\"`, `\\`, `\n`, `\r` and `\t` escapes. An unquoted path must start with `/`.
+fn parse_meta_line(rest: &str) -> Option<(String, Vec<String>)> {
+	let rest = rest.trim_start();
+	if rest.is_empty() {
+		return None;
+	}
+	let (path, rest) = match rest.strip_prefix('"') {
+		Some(quoted) => unquote(quoted),
+		None => {
+			let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+			let path = &rest[..end];
+			assert!(path.starts_with('/'), "fixture path must start with `/` unless quoted: {path:?}");
+			(path.to_owned(), &rest[end..])
+		}
+	};
+	Some((path, parse_meta_tokens(rest)))
+}
+
+/// Split meta tokens on whitespace, keeping `key:"quoted value"` tokens whole (unquoted).
+pub(crate) fn parse_meta_tokens(mut rest: &str) -> Vec<String> {
+	let mut tokens = Vec::new();
+	loop {
+		rest = rest.trim_start();
+		if rest.is_empty() {
+			break;
+		}
+		let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+		match rest[..end].find(':').map(|idx| idx + 1).filter(|&idx| rest[idx..].starts_with('"')) {
+			Some(value_start) => {
+				let (value, after) = unquote(&rest[value_start + 1..]);
+				tokens.push(format!("{}{value}", &rest[..value_start]));
+				rest = after;
+			}
+			None => {
+				tokens.push(rest[..end].to_owned());
+				rest = &rest[end..];
+			}
+		}
+	}
+	tokens
+}
+
+/// Read a quoted string up to its closing quote, returning it unescaped and what follows it.
+fn unquote(quoted: &str) -> (String, &str) {
+	let mut text = String::new();
+	let mut chars = quoted.char_indices();
+	while let Some((idx, c)) = chars.next() {
+		match c {
+			'"' => return (text, &quoted[idx + 1..]),
+			'\\' => text.push(match chars.next().map(|(_, c)| c) {
+				Some('n') => '\n',
+				Some('r') => '\r',
+				Some('t') => '\t',
+				Some(c) => c,
+				None => break,
+			}),
+			c => text.push(c),
+		}
+	}
+	panic!("unterminated quoted string in fixture meta: \"{quoted}");
+}
+
+/// Write `path` for a `//-` line, quoting it if it contains whitespace or doesn't start with `/`.
+fn quote_path(path: &str) -> Cow<'_, str> {
+	if path.starts_with('/') { quote_value(path) } else { Cow::Owned(quote(path)) }
+}
+
+/// Write a meta value, quoting it if it contains whitespace or starts with a quote.
+fn quote_value(value: &str) -> Cow<'_, str> {
+	if !value.contains(char::is_whitespace) && !value.starts_with('"') {
+		return Cow::Borrowed(value);
+	}
+	Cow::Owned(quote(value))
+}
+
+fn quote(text: &str) -> String {
+	let mut quoted = String::from('"');
+	for c in text.chars() {
+		match c {
+			'"' => quoted.push_str("\\\""),
+			'\\' => quoted.push_str("\\\\"),
+			'\n' => quoted.push_str("\\n"),
+			'\r' => quoted.push_str("\\r"),
+			'\t' => quoted.push_str("\\t"),
+			c => quoted.push(c),
+		}
+	}
+	quoted.push('"');
+	quoted
+}
+
+/// Escape content lines that would be read as `//-` lines, by adding a backslash.
+fn escape_meta_lines(text: &str) -> Cow<'_, str> {
+	let needs_escape = |line: &str| line.trim_start_matches('\\').starts_with("//-");
+	if !text.split_inclusive('\n').any(needs_escape) {
+		return Cow::Borrowed(text);
+	}
+	let mut escaped = String::with_capacity(text.len() + 1);
+	for line in text.split_inclusive('\n') {
+		if needs_escape(line) {
+			escaped.push('\\');
+		}
+		escaped.push_str(line);
+	}
+	Cow::Owned(escaped)
 }
 
 /// Parsed fixture containing multiple files
@@ -83,7 +487,8 @@ impl Fixture {
 	///
 	/// This walks the directory recursively, skipping `.git` directories,
 	/// and creates a Fixture with all text files found. Files are sorted
-	/// by path for deterministic output.
+	/// by path for deterministic output. See [`ReadOptions::record_links_and_modes`]
+	/// for capturing symlinks and file modes.
 	///
 	/// # Arguments
 	///
@@ -102,24 +507,29 @@ impl Fixture {
 	/// let fixture = Fixture::read_from_directory("/path/to/dir").unwrap();
 	/// insta::assert_snapshot!(fixture.render());
 	/// ```
-	pub fn read_from_directory(path: impl AsRef<std::path::Path>) -> Option<Self> {
+	pub fn read_from_directory(path: impl AsRef<Path>) -> Option<Self> {
+		Self::read_from_directory_with(path, &ReadOptions::default())
+	}
+
+	/// Like [`Fixture::read_from_directory`], with [`ReadOptions`] such as size limits.
+	pub fn read_from_directory_with(path: impl AsRef<Path>, options: &ReadOptions) -> Option<Self> {
 		let path = path.as_ref();
 		if !path.exists() {
 			return None;
 		}
 
 		let mut files = Vec::new();
-		for entry in walkdir::WalkDir::new(path)
-			.into_iter()
-			.filter_entry(|e| !e.path().to_string_lossy().contains(".git"))
-			.filter_map(Result::ok)
-		{
+		for entry in walk_without_git(walkdir::WalkDir::new(path)) {
 			let entry_path = entry.path();
-			if entry_path.is_file() {
+			if is_captured(&entry, options.links_and_modes) {
 				let relative_path = entry_path.strip_prefix(path).expect("path should be under base");
 				let relative_str = format!("/{}", relative_path.to_string_lossy());
-				if let Ok(text) = fs::read_to_string(entry_path) {
-					files.push(FixtureFile { path: relative_str, text });
+				if !options.filter.matches(&relative_str) {
+					continue;
+				}
+				let limit = options.limits.for_path(&relative_str);
+				if let Some((text, meta)) = capture_file(entry_path, options.links_and_modes, limit) {
+					files.push(FixtureFile { path: relative_str, text, meta });
 				}
 			}
 		}
@@ -143,69 +553,87 @@ impl Fixture {
 
 		let mut files = Vec::new();
 
-		if !fixture.contains("//-") {
+		if !fixture.lines().any(|line| line.starts_with("//-")) {
 			// Single file fixture - treat as default path
 			return Self {
-				files: vec![FixtureFile {
-					path: default_path.to_owned(),
-					text: fixture.to_owned(),
-				}],
+				files: vec![FixtureFile::new(default_path.to_owned(), fixture.to_owned())],
 			};
 		}
 
-		let mut current_path: Option<String> = None;
+		let mut current: Option<(String, FileMeta, bool)> = None;
 		let mut current_text = String::new();
+		let mut finish = |current: Option<(String, FileMeta, bool)>, mut text: String| {
+			if let Some((path, meta, no_eol)) = current {
+				if no_eol {
+					text.pop();
+				}
+				files.push(FixtureFile { path, text, meta });
+			}
+		};
 
 		for line in fixture.split_inclusive('\n') {
 			if let Some(rest) = line.strip_prefix("//-") {
 				// Save previous file if any
-				if let Some(path) = current_path.take() {
-					files.push(FixtureFile {
-						path,
-						text: std::mem::take(&mut current_text),
-					});
-				}
-
-				// Parse new file path
-				let meta = rest.trim();
-				let path = meta.split_whitespace().next().expect("fixture meta must have a path");
-				assert!(path.starts_with('/'), "fixture path must start with `/`: {path:?}");
-				current_path = Some(path.to_owned());
-			} else if current_path.is_some() {
-				current_text.push_str(line);
+				finish(current.take(), std::mem::take(&mut current_text));
+
+				// Parse new file path and attributes; a bare `//-` only ends the previous file
+				let Some((path, tokens)) = parse_meta_line(rest) else { continue };
+				let no_eol = tokens.iter().any(|token| token == "no-eol");
+				current = Some((path, FileMeta::parse(tokens.iter().map(String::as_str)), no_eol));
+			} else if current.is_some() {
+				// `\//-` escapes a content line starting with `//-`
+				let unescaped = line.starts_with('\\') && line.trim_start_matches('\\').starts_with("//-");
+				current_text.push_str(if unescaped { &line[1..] } else { line });
 			}
 		}
 
 		// Save last file
-		if let Some(path) = current_path {
-			files.push(FixtureFile { path, text: current_text });
-		}
+		finish(current, current_text);
 
 		Self { files }
 	}
 
 	/// Write fixture files to a temporary directory and return the path
+	///
+	/// The directory is named after the running test and created in the system
+	/// temp dir, or in `V_FIXTURES_TMPDIR` if set; see [`TempDirOptions`].
 	pub fn write_to_tempdir(&self) -> TempFixture {
-		self.write_to_tempdir_with_prefix("v_fixture_")
+		self.write_to_tempdir_with(&TempDirOptions::new())
 	}
 
 	/// Write fixture files to a temporary directory with a custom prefix
+	///
+	/// The directory name is `prefix` followed by random characters, without the test name.
 	pub fn write_to_tempdir_with_prefix(&self, prefix: &str) -> TempFixture {
-		let temp_dir = tempfile::Builder::new().prefix(prefix).tempdir().expect("failed to create temp dir");
+		self.write_to_tempdir_with(&TempDirOptions::new().prefix(prefix).without_test_name())
+	}
+
+	/// Copy of the fixture with `{{ROOT}}` and `{{CWD}}` in file contents replaced by the given paths.
+	fn expand_placeholders(&self, root: &Path, cwd: &Path) -> Fixture {
+		let files = self
+			.files
+			.iter()
+			.map(|f| FixtureFile {
+				text: expand_placeholders(&f.text, root, cwd).into_owned(),
+				..f.clone()
+			})
+			.collect();
+		Fixture { files }
+	}
 
+	/// Write fixture files under `root`, creating it and any parent dirs as needed.
+	///
+	/// Existing files at the same paths are overwritten; other files are left alone.
+	/// Contents are written as is; `{{ROOT}}` placeholders are only expanded by
+	/// [`Fixture::write_to_tempdir`].
+	pub fn write_to_directory(&self, root: impl AsRef<Path>) {
+		let root = root.as_ref();
 		for file in &self.files {
-			let path = temp_dir.path().join(file.path.trim_start_matches('/'));
+			let path = root.join(file.path.trim_start_matches('/'));
 			if let Some(parent) = path.parent() {
 				fs::create_dir_all(parent).expect("failed to create parent dirs");
 			}
-			fs::write(&path, &file.text).expect("failed to write fixture file");
-		}
-
-		TempFixture {
-			root: temp_dir.path().to_path_buf(),
-			temp_dir,
-			files: self.files.clone(),
-			cwd: None,
+			write_file(&path, file);
 		}
 	}
 
@@ -255,13 +683,28 @@ impl Fixture {
 	pub fn render(&self) -> String {
 		FixtureRenderer::new(self).render()
 	}
+
+	/// Render the fixture losslessly, so that [`Fixture::parse`] gives it back exactly.
+	///
+	/// See [`FixtureRenderer::lossless`]. Useful for saving captured directories as
+	/// fixture files and reloading them later.
+	pub fn render_lossless(&self) -> String {
+		FixtureRenderer::new(self).lossless().render()
+	}
 }
 
 /// A fixture written to a temporary directory
+///
+/// The directory is removed on drop, unless a test is failing and
+/// [`keep_on_failure`](Self::keep_on_failure) or `V_FIXTURES_KEEP=1` asks to keep it
+/// (moved aside with a `_kept` suffix).
 #[derive(derive_new::new)]
 pub struct TempFixture {
 	/// Root path of the temporary directory
 	pub root: PathBuf,
+	/// Keeps the directory when dropped during a panic, see [`TempFixture::keep_on_failure`]
+	#[new(value = "keep::KeepGuard::new(temp_dir.path())")]
+	keep: keep::KeepGuard,
 	/// The temp directory handle - kept alive to preserve the directory
 	pub temp_dir: tempfile::TempDir,
 	/// Original files that were written
@@ -271,13 +714,20 @@ pub struct TempFixture {
 	/// paths to include and how to format them.
 	#[new(default)]
 	cwd: Option<PathBuf>,
+	/// The cwd `{{CWD}}` was expanded to when writing, if not the root
+	#[new(default)]
+	written_cwd: Option<PathBuf>,
 }
 
 impl TempFixture {
 	/// Set the current working directory for path resolution.
 	///
 	/// When `read_all_from_disk` is called, only files under this directory
-	/// will be included, and paths will be relative to it.
+	/// will be included, and paths will be relative to it. Reads turn the
+	/// directory's absolute path in contents into `{{CWD}}`.
+	///
+	/// Nothing on disk changes, so files were written with `{{CWD}}` expanded
+	/// to the root; use [`TempDirOptions::cwd`] to expand it to this directory.
 	///
 	/// # Example
 	///
@@ -303,7 +753,7 @@ impl TempFixture {
 	}
 
 	/// Get the effective cwd path (absolute)
-	fn effective_cwd(&self) -> PathBuf {
+	pub(crate) fn effective_cwd(&self) -> PathBuf {
 		match &self.cwd {
 			Some(cwd) => self.root.join(cwd),
 			None => self.root.clone(),
@@ -315,14 +765,17 @@ impl TempFixture {
 		self.root.join(relative.trim_start_matches('/'))
 	}
 
-	/// Read a file's current contents
+	/// Read a file's current contents (decompressed if stored compressed)
+	///
+	/// The contents are returned as on disk, with absolute temp paths left in;
+	/// [`read_all_from_disk`](Self::read_all_from_disk) turns them into placeholders.
 	pub fn read(&self, relative: &str) -> String {
-		fs::read_to_string(self.path(relative)).expect("failed to read file")
+		self.try_read(relative).expect("failed to read file")
 	}
 
-	/// Try to read a file's current contents
+	/// Try to read a file's current contents (decompressed if stored compressed)
 	pub fn try_read(&self, relative: &str) -> Option<String> {
-		fs::read_to_string(self.path(relative)).ok()
+		read_text_file(&self.path(relative)).map(|(text, _)| text)
 	}
 
 	/// Write content to a file (creates parent dirs if needed)
@@ -363,14 +816,56 @@ impl TempFixture {
 		path
 	}
 
+	/// Absolute paths that captured contents mention as placeholders, most specific first.
+	fn placeholder_paths(&self) -> Vec<(PathBuf, &'static str)> {
+		let mut paths = Vec::new();
+		if self.cwd.is_some() {
+			paths.push((self.effective_cwd(), CWD_PLACEHOLDER));
+		}
+		paths.push((self.root.clone(), ROOT_PLACEHOLDER));
+		paths
+	}
+
+	/// Turn absolute temp paths in `text` back into placeholders.
+	fn collapse(&self, text: String) -> String {
+		let paths = self.placeholder_paths();
+		let paths: Vec<(&Path, &str)> = paths.iter().map(|(path, placeholder)| (path.as_path(), *placeholder)).collect();
+		collapse_placeholders(text, &paths)
+	}
+
+	/// Read a captured file's text, with absolute temp paths turned back into placeholders.
+	fn read_captured(&self, path: &Path, links_and_modes: bool, limit: Option<SizeLimit>) -> Option<(String, FileMeta)> {
+		let (text, meta) = capture_file(path, links_and_modes, limit)?;
+		Some((self.collapse(text), meta))
+	}
+
+	/// The written [`files`](Self::files) as a read would capture them unchanged:
+	/// placeholders expanded like on disk, then collapsed like in reads.
+	fn written_as_read(&self) -> Fixture {
+		let cwd = self.written_cwd.as_ref().map_or_else(|| self.root.clone(), |cwd| self.root.join(cwd));
+		let files = self
+			.files
+			.iter()
+			.map(|f| FixtureFile {
+				text: self.collapse(expand_placeholders(&f.text, &self.root, &cwd).into_owned()),
+				..f.clone()
+			})
+			.collect();
+		Fixture { files }
+	}
+
 	/// Read all original files and return as a new Fixture
+	///
+	/// Like [`read`](Self::read), contents are returned as on disk. Files written
+	/// as symlinks or with a mode are read back as such.
 	pub fn read_all(&self) -> Fixture {
 		let files = self
 			.files
 			.iter()
 			.map(|f| {
-				let text = self.read(&f.path);
-				FixtureFile { path: f.path.clone(), text }
+				let links_and_modes = f.meta.symlink.is_some() || f.meta.mode.is_some();
+				let (text, meta) = capture_file(&self.path(&f.path), links_and_modes, None).expect("failed to read file");
+				FixtureFile { path: f.path.clone(), text, meta }
 			})
 			.collect();
 		Fixture { files }
@@ -381,17 +876,118 @@ impl TempFixture {
 	///
 	/// If `cwd` is set, only files under that directory are included and paths
 	/// are relative to it. Otherwise, all files under root are included.
+	///
+	/// Absolute paths of the working directory (if set) and the temp root in
+	/// contents are replaced with `{{CWD}}` and `{{ROOT}}`, so snapshots don't
+	/// depend on the random temp dir name.
 	pub fn read_all_from_disk(&self) -> Fixture {
+		self.read_all_from_disk_with(&ReadOptions::default())
+	}
+
+	/// Like [`TempFixture::read_all_from_disk`], with [`ReadOptions`] such as size limits.
+	///
+	/// Limits are looked up by the captured path, i.e. relative to the cwd if set.
+	pub fn read_all_from_disk_with(&self, options: &ReadOptions) -> Fixture {
+		self.capture_below(&self.effective_cwd(), options, |_| options.links_and_modes)
+	}
+
+	/// Compare the originally written [`files`](Self::files) with what is on disk now.
+	///
+	/// Paths are relative to the root, regardless of the cwd. Both sides have
+	/// absolute temp paths collapsed into placeholders the same way, so how a
+	/// fixture spelled them doesn't count as a change.
+	pub fn changes(&self) -> FixtureDiff {
+		self.written_as_read().diff(&self.capture_current())
+	}
+
+	/// Capture everything below the root the way [`read_all`](Self::read_all) reads
+	/// each written file: files written as symlinks or with a mode are read as such,
+	/// everything else is followed.
+	fn capture_current(&self) -> Fixture {
+		self.capture_below(&self.root, &ReadOptions::default(), |path| {
+			self.files.iter().any(|f| f.path == path && (f.meta.symlink.is_some() || f.meta.mode.is_some()))
+		})
+	}
+
+	/// Render only the files that changed on disk since they were written, with their current content.
+	///
+	/// Each file is headed by its status: `(added)`, `(modified)`, `(removed)` or
+	/// `(renamed from /old/path)`. Removed files have no content. Nothing changed
+	/// renders as an empty string. See [`render_changes_as_diff`](Self::render_changes_as_diff)
+	/// to show only the changed lines.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::Fixture;
+	///
+	/// let temp = Fixture::parse("//- /keep.txt\nsame\n//- /config.toml\nlevel = 1\n//- /old.log\nx\n").write_to_tempdir();
+	/// temp.write("/config.toml", "level = 2\n");
+	/// temp.write("/out/result.txt", "ok\n");
+	/// std::fs::remove_file(temp.path("/old.log")).unwrap();
+	///
+	/// assert_eq!(
+	///     temp.render_changes(),
+	///     "//- /config.toml (modified)\nlevel = 2\n//- /old.log (removed)\n//- /out/result.txt (added)\nok\n"
+	/// );
+	/// ```
+	pub fn render_changes(&self) -> String {
+		let current = self.capture_current();
+		let mut out = String::new();
+		for change in self.written_as_read().diff(&current).changes {
+			let path = change.path();
+			match &change {
+				FileChange::Renamed { from, .. } => out.push_str(&format!("//- {path} (renamed from {from})\n")),
+				_ => out.push_str(&format!("//- {path} ({})\n", change.status())),
+			}
+			if matches!(change, FileChange::Removed { .. }) {
+				continue;
+			}
+			let text = &current.file(path).expect("changed file should be on disk").text;
+			out.push_str(text);
+			if !text.is_empty() && !text.ends_with('\n') {
+				out.push('\n');
+			}
+		}
+		out
+	}
+
+	/// Render the changes on disk since the files were written as a `git diff`-style unified diff.
+	///
+	/// Added and removed files are marked with `new file mode`/`deleted file mode`
+	/// lines, renames with `rename from`/`rename to`.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::Fixture;
+	///
+	/// let temp = Fixture::parse("//- /config.toml\nname = \"app\"\nlevel = 1\n").write_to_tempdir();
+	/// temp.write("/config.toml", "name = \"app\"\nlevel = 2\n");
+	/// assert!(temp.render_changes_as_diff().contains("-level = 1\n+level = 2\n"));
+	/// ```
+	pub fn render_changes_as_diff(&self) -> String {
+		self.changes().render_unified()
+	}
+
+	/// Capture the files below `base`, with paths relative to it and sorted.
+	///
+	/// `links_and_modes` decides per relative path whether symlinks and modes are
+	/// recorded, like [`ReadOptions::record_links_and_modes`] does for all of them.
+	fn capture_below(&self, base: &Path, options: &ReadOptions, links_and_modes: impl Fn(&str) -> bool) -> Fixture {
 		let mut files: Vec<FixtureFile> = Vec::new();
-		let base = self.effective_cwd();
 
-		for entry in walkdir::WalkDir::new(&base).into_iter().filter_map(Result::ok) {
+		for entry in walk_without_git(walkdir::WalkDir::new(base)) {
 			let path = entry.path();
-			if path.is_file() {
-				let relative_path = path.strip_prefix(&base).expect("path should be under base");
-				let relative_str = format!("/{}", relative_path.to_string_lossy());
-				if let Ok(text) = fs::read_to_string(path) {
-					files.push(FixtureFile { path: relative_str, text });
+			let relative_path = path.strip_prefix(base).expect("path should be under base");
+			let relative_str = format!("/{}", relative_path.to_string_lossy());
+			let links_and_modes = links_and_modes(&relative_str);
+			if is_captured(&entry, links_and_modes) {
+				if !options.filter.matches(&relative_str) {
+					continue;
+				}
+				if let Some((text, meta)) = self.read_captured(path, links_and_modes, options.limits.for_path(&relative_str)) {
+					files.push(FixtureFile { path: relative_str, text, meta });
 				}
 			}
 		}
@@ -421,15 +1017,41 @@ impl TempFixture {
 /// assert!(before.files[0].text.contains("let x"));
 /// assert!(after.files[0].text.contains("let y"));
 /// ```
+///
+/// Use [`BeforeAfter`] for another separator or default paths.
+#[track_caller]
 pub fn parse_before_after(fixture: &str) -> (Fixture, Fixture) {
-	let fixture = trim_indent(fixture);
-	let parts: Vec<&str> = fixture.split("\n=>\n").collect();
-	assert_eq!(parts.len(), 2, "expected exactly one `=>` separator in before/after fixture");
-
-	let before = Fixture::parse(parts[0]);
-	let after = Fixture::parse(parts[1]);
+	BeforeAfter::new().parse(fixture)
+}
 
-	(before, after)
+/// Parse a before/after fixture whose `=>` half is a patch applied to the before half.
+///
+/// The after half accepts anything [`Fixture::apply_patch`] does. Useful when the
+/// expected output is a small edit of a large base tree.
+///
+/// Panics if the patch doesn't apply.
+///
+/// # Example
+///
+/// ```
+/// use v_fixtures::parse_before_patch;
+///
+/// let (before, after) = parse_before_patch(r#"
+///     //- /test.rs
+///     fn main() { let x = 1; }
+///     =>
+///     --- a/test.rs
+///     +++ b/test.rs
+///     @@ -1 +1 @@
+///     -fn main() { let x = 1; }
+///     +fn main() { let y = 1; }
+/// "#);
+/// assert!(before.files[0].text.contains("let x"));
+/// assert!(after.files[0].text.contains("let y"));
+/// ```
+#[track_caller]
+pub fn parse_before_patch(fixture: &str) -> (Fixture, Fixture) {
+	BeforeAfter::new().parse_patch(fixture)
 }
 
 /// Remove common leading indentation from all lines.
@@ -446,39 +1068,15 @@ pub fn trim_indent(text: &str) -> String {
 		.collect()
 }
 
-/// Compare two fixtures for equality, with nice diff output on failure
-#[track_caller]
-pub fn assert_fixture_eq(expected: &Fixture, actual: &Fixture) {
-	if expected.files.len() != actual.files.len() {
-		panic!(
-			"fixture file count mismatch: expected {} files, got {}\nExpected: {:?}\nActual: {:?}",
-			expected.files.len(),
-			actual.files.len(),
-			expected.files.iter().map(|f| &f.path).collect::<Vec<_>>(),
-			actual.files.iter().map(|f| &f.path).collect::<Vec<_>>()
-		);
-	}
-
-	for expected_file in &expected.files {
-		let actual_file = actual.file(&expected_file.path).unwrap_or_else(|| {
-			panic!(
-				"missing file in actual: {}\nActual files: {:?}",
-				expected_file.path,
-				actual.files.iter().map(|f| &f.path).collect::<Vec<_>>()
-			)
-		});
+pub mod fs_standards;
+#[cfg(feature = "insta")]
+pub mod snapshot;
 
-		if expected_file.text != actual_file.text {
-			panic!(
-				"file {} content mismatch:\n\n--- Expected ---\n{}\n--- Actual ---\n{}\n",
-				expected_file.path, expected_file.text, actual_file.text
-			);
-		}
-	}
+/// Entries of `walk`, skipping `.git` directories like every directory read does.
+pub(crate) fn walk_without_git(walk: walkdir::WalkDir) -> impl Iterator<Item = walkdir::DirEntry> {
+	walk.into_iter().filter_entry(|e| e.depth() == 0 || e.file_name() != ".git").filter_map(Result::ok)
 }
 
-pub mod fs_standards;
-
 /// Builder for rendering fixtures with various normalizations.
 ///
 /// # Example
@@ -497,9 +1095,24 @@ pub struct FixtureRenderer<'a> {
 	fixture: &'a Fixture,
 	normalize_git_hashes: bool,
 	lines_to_redact: Vec<usize>,
+	file_lines_to_redact: Vec<(String, Vec<usize>)>,
 	redact_message: Cow<'static, str>,
 	path_patterns: Vec<PathPattern>,
+	path_filter: PathFilter,
 	always_show_filepath: bool,
+	canonicalize_structured: bool,
+	content_redactions: Vec<ContentRedaction>,
+	presets: Vec<RedactionPreset>,
+	placeholder_paths: Vec<(PathBuf, &'static str)>,
+	tree: TreeMode,
+	file_stats: bool,
+	limits: Limits,
+	markdown: bool,
+	lossless: bool,
+	transforms: Vec<Transform<'a>>,
+	resolve_carriage_returns: bool,
+	strip_ansi: bool,
+	trim_trailing_whitespace: bool,
 }
 impl<'a> FixtureRenderer<'a> {
 	/// Create a new renderer for the given fixture.
@@ -508,9 +1121,24 @@ impl<'a> FixtureRenderer<'a> {
 			fixture,
 			normalize_git_hashes: false,
 			lines_to_redact: Vec::new(),
+			file_lines_to_redact: Vec::new(),
 			redact_message: Cow::Borrowed("[REDACTED]"),
 			path_patterns: Vec::new(),
+			path_filter: PathFilter::new(),
 			always_show_filepath: false,
+			canonicalize_structured: false,
+			content_redactions: Vec::new(),
+			presets: Vec::new(),
+			placeholder_paths: Vec::new(),
+			tree: TreeMode::Off,
+			file_stats: false,
+			limits: Limits::default(),
+			markdown: false,
+			lossless: false,
+			transforms: Vec::new(),
+			resolve_carriage_returns: false,
+			strip_ansi: false,
+			trim_trailing_whitespace: false,
 		}
 	}
 
@@ -528,12 +1156,42 @@ impl<'a> FixtureRenderer<'a> {
 	/// Line numbers are 1-indexed and refer to the final rendered output.
 	/// Redacted lines are replaced with the redact message (default: "[REDACTED]").
 	///
-	/// Useful for non-deterministic values like timestamps.
+	/// Useful for non-deterministic values like timestamps. Since the numbers
+	/// shift when earlier files change, [`redact_file_lines`](Self::redact_file_lines)
+	/// or [`redact_regex`](Self::redact_regex) are usually more robust.
 	pub fn redact_lines(mut self, lines: &[usize]) -> Self {
 		self.lines_to_redact = lines.to_vec();
 		self
 	}
 
+	/// Redact lines of the file at `path`, numbered within that file's content.
+	///
+	/// Line numbers are 1-indexed and relative to the file (after canonicalization,
+	/// if [`canonicalize_structured`](Self::canonicalize_structured) is on), so they
+	/// stay put regardless of headers, path filters and the other files. Redacted
+	/// lines are replaced with the redact message; multiple calls accumulate.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::{Fixture, FixtureRenderer};
+	///
+	/// let fixture = Fixture::parse(r#"
+	/// //- /a.txt
+	/// a
+	/// //- /stamp.txt
+	/// built
+	/// 12:00:00
+	/// "#);
+	///
+	/// let output = FixtureRenderer::new(&fixture).redact_file_lines("/stamp.txt", &[2]).render();
+	/// assert!(output.ends_with("//- /stamp.txt\nbuilt\n[REDACTED]\n"));
+	/// ```
+	pub fn redact_file_lines(mut self, path: &str, lines: &[usize]) -> Self {
+		self.file_lines_to_redact.push((path.to_owned(), lines.to_vec()));
+		self
+	}
+
 	/// Set a custom message for redacted lines.
 	///
 	/// Default is "[REDACTED]".
@@ -542,12 +1200,258 @@ impl<'a> FixtureRenderer<'a> {
 		self
 	}
 
+	/// Redact values matched by a built-in preset, such as UUIDs or timestamps.
+	///
+	/// Values become numbered placeholders like `[UUID-1]`, numbered in order of
+	/// first appearance over the whole render, so equal values stay visibly equal.
+	/// Presets apply to file contents after [`redact_regex`](Self::redact_regex)
+	/// redactions, in [`RedactionPreset`] declaration order.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::{Fixture, FixtureRenderer, RedactionPreset};
+	///
+	/// let fixture = Fixture::parse(r#"
+	/// //- /events.log
+	/// 2026-01-22T12:00:00Z start 67e55044-10b1-426f-9247-bb680e5fe0c8
+	/// 2026-01-22T12:00:05Z stop 67e55044-10b1-426f-9247-bb680e5fe0c8 after 5s
+	/// "#);
+	///
+	/// let output = FixtureRenderer::new(&fixture)
+	///     .redact_preset(RedactionPreset::Uuid)
+	///     .redact_preset(RedactionPreset::Timestamp)
+	///     .redact_preset(RedactionPreset::Duration)
+	///     .render();
+	/// assert_eq!(output, "[TIMESTAMP-1] start [UUID-1]\n[TIMESTAMP-2] stop [UUID-1] after [DURATION-1]\n");
+	/// ```
+	pub fn redact_preset(mut self, preset: RedactionPreset) -> Self {
+		if let Err(idx) = self.presets.binary_search(&preset) {
+			self.presets.insert(idx, preset);
+		}
+		self
+	}
+
+	/// Enable every [`RedactionPreset`].
+	pub fn redact_all_presets(mut self) -> Self {
+		self.presets = RedactionPreset::ALL.to_vec();
+		self
+	}
+
+	/// Replace occurrences of the absolute path `root` in contents with `{{ROOT}}`.
+	///
+	/// [`TempFixture::read_all_from_disk`] already does this for its temp dir; use
+	/// this for fixtures captured otherwise, e.g. with [`Fixture::read_from_directory`].
+	pub fn root_placeholder(self, root: impl Into<PathBuf>) -> Self {
+		self.push_placeholder_path(root.into(), ROOT_PLACEHOLDER)
+	}
+
+	/// Replace occurrences of the absolute path `cwd` in contents with `{{CWD}}`.
+	pub fn cwd_placeholder(self, cwd: impl Into<PathBuf>) -> Self {
+		self.push_placeholder_path(cwd.into(), CWD_PLACEHOLDER)
+	}
+
+	fn push_placeholder_path(mut self, path: PathBuf, placeholder: &'static str) -> Self {
+		self.placeholder_paths.push((path, placeholder));
+		// Longer paths first, so a cwd under the root becomes `{{CWD}}` rather than `{{ROOT}}/...`
+		self.placeholder_paths.sort_by_key(|(path, _)| std::cmp::Reverse(path.as_os_str().len()));
+		self
+	}
+
+	/// Start the output with a `tree`-style listing of the rendered files.
+	///
+	/// The listing shows directories, files with their sizes in bytes (of the
+	/// rendered content), and modes or link targets when the file meta has them.
+	/// File contents follow, always with headers.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::{Fixture, FixtureRenderer};
+	///
+	/// let fixture = Fixture::parse(r#"
+	/// //- /src/main.rs
+	/// fn main() {}
+	/// //- /Cargo.toml
+	/// [package]
+	/// "#);
+	///
+	/// let output = FixtureRenderer::new(&fixture).tree_only().render();
+	/// assert_eq!(output, ".\n├── Cargo.toml (10 bytes)\n└── src/\n    └── main.rs (13 bytes)\n");
+	/// ```
+	pub fn tree(mut self) -> Self {
+		self.tree = TreeMode::WithContents;
+		self
+	}
+
+	/// Render only the [`tree`](Self::tree) listing, without file contents.
+	///
+	/// Useful when the shape of the tree is all a snapshot should pin.
+	pub fn tree_only(mut self) -> Self {
+		self.tree = TreeMode::Only;
+		self
+	}
+
+	/// Add the line count and a content hash to each file's header, as `lines:N hash:H`.
+	///
+	/// Both describe the rendered content, so they're stable under redaction.
+	/// The hash is a 64-bit FNV-1a in hex. Headers are always shown.
+	pub fn file_stats(mut self) -> Self {
+		self.file_stats = true;
+		self
+	}
+
+	/// Cap every file's rendered content at `limit`.
+	///
+	/// Oversized content renders as its head and tail around a
+	/// `[... N lines elided ...]` marker, or as a summary (see [`SizeLimit`]).
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::{Fixture, FixtureRenderer, SizeLimit};
+	///
+	/// let fixture = Fixture::parse("//- /out.log\n1\n2\n3\n4\n//- /small.txt\nx\n");
+	/// let output = FixtureRenderer::new(&fixture).limit(SizeLimit::lines(2)).limit_for("*.log", SizeLimit::lines(1).summary()).render();
+	/// assert_eq!(output, "//- /out.log\n[4 lines, 8 bytes, hash b088f39c8dcc30a9]\n//- /small.txt\nx\n");
+	/// ```
+	pub fn limit(mut self, limit: SizeLimit) -> Self {
+		self.limits.default = Some(limit);
+		self
+	}
+
+	/// Use `limit` for files matching `glob` instead.
+	///
+	/// Globs work like in [`PathFilter`]; when several match, the last one added wins.
+	pub fn limit_for(mut self, glob: &str, limit: SizeLimit) -> Self {
+		self.limits.overrides.push((PathFilter::new().include(glob), Some(limit)));
+		self
+	}
+
+	/// Render files matching `glob` in full, despite [`limit`](Self::limit).
+	pub fn unlimited_for(mut self, glob: &str) -> Self {
+		self.limits.overrides.push((PathFilter::new().include(glob), None));
+		self
+	}
+
+	/// Render as Markdown: a heading per file, then a fenced code block tagged with
+	/// a language inferred from the extension.
+	///
+	/// Meta tokens follow the path in the heading; the tree listing, if enabled,
+	/// comes first in its own code block. [`Fixture::parse_markdown`] reads the
+	/// output back.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::{Fixture, FixtureRenderer};
+	///
+	/// let fixture = Fixture::parse("//- /src/main.rs\nfn main() {}\n");
+	/// let output = FixtureRenderer::new(&fixture).markdown().render();
+	/// assert_eq!(output, "### `/src/main.rs`\n\n```rust\nfn main() {}\n```\n");
+	/// assert_eq!(Fixture::parse_markdown(&output), fixture);
+	/// ```
+	pub fn markdown(mut self) -> Self {
+		self.markdown = true;
+		self
+	}
+
+	/// Render so that [`Fixture::parse`] gives back exactly the rendered files.
+	///
+	/// Every file gets a header, with the path quoted if it contains whitespace or
+	/// doesn't start with `/` (and a symlink target quoted if it contains whitespace).
+	/// Content lines starting with `//-` are escaped as `\//-`, a missing final
+	/// newline is marked with a `no-eol` flag, and an empty fixture renders as a
+	/// bare `//-` line. Transformations like redaction still apply, so combine
+	/// them with this mode only if the output needn't match the fixture.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::{Fixture, FixtureFile, FixtureRenderer};
+	///
+	/// let fixture = Fixture {
+	///     files: vec![FixtureFile::new("/my notes.txt".to_owned(), "//- not a header\nno newline".to_owned())],
+	/// };
+	/// let output = FixtureRenderer::new(&fixture).lossless().render();
+	/// assert_eq!(output, "//- \"/my notes.txt\" no-eol\n\\//- not a header\nno newline\n");
+	/// assert_eq!(Fixture::parse(&output), fixture);
+	/// ```
+	pub fn lossless(mut self) -> Self {
+		self.lossless = true;
+		self
+	}
+
+	/// Remove ANSI escape sequences (colors, cursor movement, titles, hyperlinks) from file contents.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::{Fixture, FixtureRenderer};
+	///
+	/// let fixture = Fixture::parse("//- /out.log\n\x1b[1;32mPASS\x1b[0m all tests\n");
+	/// assert_eq!(FixtureRenderer::new(&fixture).strip_ansi().render(), "PASS all tests\n");
+	/// ```
+	pub fn strip_ansi(mut self) -> Self {
+		self.strip_ansi = true;
+		self
+	}
+
+	/// Resolve `\r` overwrites in file contents the way a terminal shows them.
+	///
+	/// Text after a lone `\r` overwrites the start of its line, so a progress bar
+	/// collapses to its final state. Erase-in-line sequences like `ESC[K` are
+	/// honored; `\r\n` line endings are left alone. This runs before
+	/// [`strip_ansi`](Self::strip_ansi), which then removes the remaining sequences.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::{Fixture, FixtureRenderer};
+	///
+	/// let fixture = Fixture::parse("//- /out.log\n[   ] 0%\r[#  ] 33%\r[###] 100%\ndone\n");
+	/// assert_eq!(FixtureRenderer::new(&fixture).resolve_carriage_returns().render(), "[###] 100%\ndone\n");
+	/// ```
+	pub fn resolve_carriage_returns(mut self) -> Self {
+		self.resolve_carriage_returns = true;
+		self
+	}
+
+	/// Remove spaces and tabs at the end of every line of file contents.
+	///
+	/// Runs after [`resolve_carriage_returns`](Self::resolve_carriage_returns) and
+	/// [`strip_ansi`](Self::strip_ansi), so padding exposed by either is trimmed too.
+	pub fn trim_trailing_whitespace(mut self) -> Self {
+		self.trim_trailing_whitespace = true;
+		self
+	}
+
 	/// Always show filepath headers, even for single-file fixtures.
 	///
-	/// By default, single-file fixtures render without the `//- path` header.
-	/// This forces the header to always be included.
-	pub fn always_show_filepath(mut self) -> Self {
-		self.always_show_filepath = true;
+	/// By default, single-file fixtures render without the `//- path` header.
+	/// This forces the header to always be included.
+	pub fn always_show_filepath(mut self) -> Self {
+		self.always_show_filepath = true;
+		self
+	}
+
+	/// Canonicalize JSON, TOML and YAML files: sort keys and pretty-print them stably.
+	///
+	/// The format is chosen like for comparison (see [`CompareMode::for_file`]).
+	/// Files that fail to parse are rendered unchanged.
+	///
+	/// # Example
+	///
+	/// ```ignore
+	/// let fixture = Fixture::parse(r#"
+	/// //- /out.json
+	/// {"b": 1, "a": [true]}
+	/// "#);
+	/// let output = FixtureRenderer::new(&fixture).canonicalize_structured().render();
+	/// assert_eq!(output, "{\n  \"a\": [\n    true\n  ],\n  \"b\": 1\n}\n");
+	/// ```
+	pub fn canonicalize_structured(mut self) -> Self {
+		self.canonicalize_structured = true;
 		self
 	}
 
@@ -588,12 +1492,119 @@ impl<'a> FixtureRenderer<'a> {
 	/// assert!(!output.contains("/tests/"));
 	/// ```
 	pub fn regex(mut self, pattern: &str) -> Self {
-		let (pattern, exclude) = match pattern.strip_prefix('!') {
-			Some(rest) => (rest, true),
-			None => (pattern, false),
-		};
-		let regex = regex::Regex::new(pattern).expect("invalid regex pattern");
-		self.path_patterns.push(PathPattern { regex, exclude });
+		self.path_patterns.push(PathPattern::parse(pattern));
+		self
+	}
+
+	/// Only render files passing the gitignore-style glob `filter`.
+	///
+	/// Applies on top of any [`regex`](Self::regex) patterns; a later call replaces the filter.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::{Fixture, FixtureRenderer, PathFilter};
+	///
+	/// let fixture = Fixture::parse(r#"
+	/// //- /src/main.rs
+	/// fn main() {}
+	/// //- /src/generated/schema.rs
+	/// // generated
+	/// //- /README.md
+	/// readme
+	/// "#);
+	///
+	/// let filter = PathFilter::new().include("src/**/*.rs").exclude("src/generated/");
+	/// let output = FixtureRenderer::new(&fixture).filter(filter).render();
+	/// assert_eq!(output, "fn main() {}\n");
+	/// ```
+	pub fn filter(mut self, filter: PathFilter) -> Self {
+		self.path_filter = filter;
+		self
+	}
+
+	/// Rewrite the content of files whose path matches `path_pattern` with `transform`.
+	///
+	/// Patterns work like in [`regex`](Self::regex) (`""` matches every file).
+	/// Transforms run first, on the file's own text, in the order they were added;
+	/// canonicalization, redaction and size limits apply to their output.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::{Fixture, FixtureRenderer};
+	///
+	/// let fixture = Fixture::parse(r#"
+	/// //- /index.txt
+	/// b
+	/// a
+	/// //- /app.lock
+	/// generated-at = 12:00
+	/// dep = 1
+	/// "#);
+	///
+	/// let output = FixtureRenderer::new(&fixture)
+	///     .transform("index", |text| {
+	///         let mut lines: Vec<_> = text.lines().collect();
+	///         lines.sort();
+	///         lines.iter().map(|l| format!("{l}\n")).collect()
+	///     })
+	///     .transform(r"\.lock$", |text| text.lines().filter(|l| !l.starts_with("generated-at")).map(|l| format!("{l}\n")).collect())
+	///     .render();
+	/// assert_eq!(output, "//- /index.txt\na\nb\n//- /app.lock\ndep = 1\n");
+	/// ```
+	pub fn transform(mut self, path_pattern: &str, transform: impl Fn(&str) -> String + 'a) -> Self {
+		self.transforms.push(Transform {
+			paths: PathPattern::parse(path_pattern),
+			apply: Box::new(transform),
+		});
+		self
+	}
+
+	/// Replace every match of `pattern` in file contents with `replacement`.
+	///
+	/// The replacement can refer to capture groups (`$1`, `${name}`), as in
+	/// [`regex::Regex::replace_all`]. Unlike [`redact_lines`](Self::redact_lines),
+	/// this follows the content, so it keeps working when other lines move.
+	/// Redactions apply in the order they were added, and not to `//-` headers.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::{Fixture, FixtureRenderer};
+	///
+	/// let fixture = Fixture::parse(r#"
+	/// //- /app.log
+	/// 12:03:44 started pid=4242
+	/// //- /other.log
+	/// pid=17
+	/// "#);
+	///
+	/// let output = FixtureRenderer::new(&fixture)
+	///     .redact_regex(r"\d{2}:\d{2}:\d{2}", "[TIME]")
+	///     .redact_regex_in("app", r"pid=(\d+)", "pid=[PID]")
+	///     .render();
+	/// assert!(output.contains("[TIME] started pid=[PID]"));
+	/// assert!(output.contains("pid=17"));
+	/// ```
+	pub fn redact_regex(self, pattern: &str, replacement: &str) -> Self {
+		self.push_content_redaction(None, pattern, replacement)
+	}
+
+	/// Like [`redact_regex`](Self::redact_regex), but only in files whose path matches `path_pattern`.
+	///
+	/// `path_pattern` works like in [`regex`](Self::regex): a substring regex,
+	/// with a leading `!` selecting files that do *not* match instead.
+	pub fn redact_regex_in(self, path_pattern: &str, pattern: &str, replacement: &str) -> Self {
+		self.push_content_redaction(Some(PathPattern::parse(path_pattern)), pattern, replacement)
+	}
+
+	fn push_content_redaction(mut self, paths: Option<PathPattern>, pattern: &str, replacement: &str) -> Self {
+		self.content_redactions.push(ContentRedaction {
+			paths,
+			regex: regex::Regex::new(pattern).expect("invalid redaction regex"),
+			replacement: replacement.to_owned(),
+		});
 		self
 	}
 
@@ -632,29 +1643,152 @@ impl<'a> FixtureRenderer<'a> {
 	fn render_raw(&self) -> String {
 		let files: Vec<_> = self.fixture.files.iter().filter(|f| self.matches_path(&f.path)).collect();
 
-		if files.len() == 1 && !self.always_show_filepath {
-			return files[0].text.clone();
-		}
+		let mut numbering = PresetNumbering::default();
+		let texts: Vec<Cow<str>> = files.iter().map(|f| self.file_text(f, &mut numbering)).collect();
 
 		let mut result = String::new();
-		for file in files {
+		if self.tree != TreeMode::Off {
+			let tree = tree::render_tree(files.iter().zip(&texts).map(|(f, text)| (*f, text.len())));
+			if self.markdown {
+				markdown::push_code_block(&mut result, "text", &tree);
+			} else {
+				result.push_str(&tree);
+			}
+			if self.tree == TreeMode::Only {
+				return result;
+			}
+		}
+
+		let show_headers = self.always_show_filepath || self.tree != TreeMode::Off || self.file_stats || self.markdown || self.lossless;
+		if let [text] = texts.as_slice()
+			&& !show_headers
+		{
+			return text.to_string();
+		}
+		if self.lossless && !self.markdown && files.is_empty() {
+			result.push_str("//-\n");
+			return result;
+		}
+
+		for (file, text) in files.into_iter().zip(texts) {
+			let mut meta_tokens = file.meta.render();
+			if self.file_stats {
+				meta_tokens.push_str(&format!(" lines:{} hash:{:016x}", text.lines().count(), tree::fnv1a64(text.as_bytes())));
+			}
+			if self.markdown {
+				markdown::push_file(&mut result, &file.path, &meta_tokens, &text);
+				continue;
+			}
+			let text = if self.lossless { escape_meta_lines(&text) } else { text };
 			result.push_str("//- ");
-			result.push_str(&file.path);
+			if self.lossless {
+				result.push_str(&quote_path(&file.path));
+			} else {
+				result.push_str(&file.path);
+			}
+			result.push_str(&meta_tokens);
+			if self.lossless && !text.is_empty() && !text.ends_with('\n') {
+				result.push_str(" no-eol");
+			}
 			result.push('\n');
-			result.push_str(&file.text);
-			if !file.text.ends_with('\n') {
+			result.push_str(&text);
+			if !(text.ends_with('\n') || self.lossless && text.is_empty()) {
 				result.push('\n');
 			}
 		}
 		result
 	}
 
+	/// Content of `file` as rendered, after per-file transformations.
+	fn file_text<'f>(&self, file: &'f FixtureFile, numbering: &mut PresetNumbering) -> Cow<'f, str> {
+		let mut text = Cow::Borrowed(file.text.as_str());
+		for transform in self.transforms.iter().filter(|t| t.paths.matches(&file.path)) {
+			text = Cow::Owned((transform.apply)(&text));
+		}
+		if self.resolve_carriage_returns
+			&& let Cow::Owned(resolved) = terminal::resolve_carriage_returns(&text)
+		{
+			text = Cow::Owned(resolved);
+		}
+		if self.strip_ansi
+			&& let Cow::Owned(stripped) = terminal::strip_ansi(&text)
+		{
+			text = Cow::Owned(stripped);
+		}
+		if self.trim_trailing_whitespace
+			&& let Cow::Owned(trimmed) = terminal::trim_trailing_whitespace(&text)
+		{
+			text = Cow::Owned(trimmed);
+		}
+		if !self.placeholder_paths.is_empty() {
+			let paths: Vec<(&Path, &str)> = self.placeholder_paths.iter().map(|(path, placeholder)| (path.as_path(), *placeholder)).collect();
+			text = Cow::Owned(collapse_placeholders(text.into_owned(), &paths));
+		}
+		if self.canonicalize_structured
+			&& let Some(canonical) = CompareMode::for_file(file).canonicalize(&text)
+		{
+			text = Cow::Owned(canonical);
+		}
+		let lines: Vec<usize> = self
+			.file_lines_to_redact
+			.iter()
+			.filter(|(path, _)| *path == file.path)
+			.flat_map(|(_, lines)| lines.iter().copied())
+			.collect();
+		if !lines.is_empty() {
+			text = Cow::Owned(
+				text.split_inclusive('\n')
+					.enumerate()
+					.map(|(i, line)| {
+						if lines.contains(&(i + 1)) {
+							format!("{}{}", self.redact_message, &line[line.trim_end_matches(['\r', '\n']).len()..])
+						} else {
+							line.to_owned()
+						}
+					})
+					.collect(),
+			);
+		}
+		if let Some(limit) = self.limits.for_path(&file.path)
+			&& let Cow::Owned(limited) = limit.apply(&text)
+		{
+			text = Cow::Owned(limited);
+		}
+		for redaction in &self.content_redactions {
+			if redaction.paths.as_ref().is_some_and(|p| !p.matches(&file.path)) {
+				continue;
+			}
+			let redacted = match redaction.regex.replace_all(&text, redaction.replacement.as_str()) {
+				Cow::Owned(redacted) => redacted,
+				Cow::Borrowed(_) => continue,
+			};
+			text = Cow::Owned(redacted);
+		}
+		for &preset in &self.presets {
+			if let Cow::Owned(redacted) = numbering.apply(preset, &text) {
+				text = Cow::Owned(redacted);
+			}
+		}
+		text
+	}
+
+	/// Paths of the files that pass the path filters, in render order.
+	#[cfg_attr(not(feature = "insta"), allow(dead_code))]
+	pub(crate) fn rendered_paths(&self) -> Vec<String> {
+		self.fixture.files.iter().filter(|f| self.matches_path(&f.path)).map(|f| f.path.clone()).collect()
+	}
+
 	/// Check if a path matches the configured patterns.
 	///
 	/// Returns true if:
 	/// - No patterns are configured, OR
 	/// - Path matches at least one inclusion pattern (if any) AND does not match any exclusion pattern
+	///
+	/// The path must also pass the glob [`PathFilter`], if one is set.
 	fn matches_path(&self, path: &str) -> bool {
+		if !self.path_filter.matches(path) {
+			return false;
+		}
 		if self.path_patterns.is_empty() {
 			return true;
 		}
@@ -676,11 +1810,48 @@ impl<'a> FixtureRenderer<'a> {
 	}
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum TreeMode {
+	Off,
+	WithContents,
+	Only,
+}
+
 struct PathPattern {
 	regex: regex::Regex,
 	exclude: bool,
 }
 
+impl PathPattern {
+	/// Parse a substring regex, where a leading `!` makes it an exclusion.
+	fn parse(pattern: &str) -> Self {
+		let (pattern, exclude) = match pattern.strip_prefix('!') {
+			Some(rest) => (rest, true),
+			None => (pattern, false),
+		};
+		let regex = regex::Regex::new(pattern).expect("invalid regex pattern");
+		Self { regex, exclude }
+	}
+
+	/// Whether `path` is selected by this pattern on its own.
+	fn matches(&self, path: &str) -> bool {
+		self.regex.is_match(path) != self.exclude
+	}
+}
+
+/// A user-supplied rewrite of the content of matching files.
+struct Transform<'a> {
+	paths: PathPattern,
+	apply: Box<dyn Fn(&str) -> String + 'a>,
+}
+
+/// A content regex replacement, optionally limited to some paths.
+struct ContentRedaction {
+	paths: Option<PathPattern>,
+	regex: regex::Regex,
+	replacement: String,
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -756,12 +1927,276 @@ mod tests {
 		assert!(after.files[0].text.contains("let y"));
 	}
 
+	#[cfg(all(feature = "gzip", feature = "zstd"))]
+	#[test]
+	fn test_parse_compress_meta() {
+		let fixture = Fixture::parse(
+			r#"
+//- /logs/app.log.gz compress:gzip
+started
+//- /cache.bin.zst compress:zstd
+cached
+//- /plain.txt crate:foo
+plain
+"#,
+		);
+		assert_eq!(fixture.files[0].meta.compress, Some(Compression::Gzip));
+		assert_eq!(fixture.files[0].text, "started\n");
+		assert_eq!(fixture.files[1].meta.compress, Some(Compression::Zstd));
+		assert_eq!(fixture.files[2].meta, FileMeta::default());
+
+		let rendered = fixture.render();
+		assert!(rendered.contains("//- /logs/app.log.gz compress:gzip\n"));
+		assert!(rendered.contains("//- /plain.txt\n"));
+		assert_eq!(Fixture::parse(&rendered), fixture);
+	}
+
+	#[test]
+	fn test_parse_file_meta() {
+		let fixture = Fixture::parse("//- /plain.txt crate:foo\nplain\n");
+		assert_eq!(fixture.files[0].meta, FileMeta::default());
+		assert_eq!(FixtureRenderer::new(&fixture).always_show_filepath().render(), "//- /plain.txt\nplain\n");
+
+		let fixture = Fixture::parse("//- /data.txt compare:text optional\n{}\n");
+		assert_eq!(fixture.files[0].meta.compare, Some(CompareMode::Text));
+		assert_eq!(FixtureRenderer::new(&fixture).always_show_filepath().render(), "//- /data.txt compare:text optional\n{}\n");
+	}
+
+	#[cfg(not(feature = "json"))]
+	#[test]
+	#[should_panic(expected = "`compare:json` requires the `json` feature of v_fixtures")]
+	fn test_compare_mode_without_feature_is_rejected() {
+		Fixture::parse("//- /data.txt compare:json\n{}\n");
+	}
+
+	#[cfg(not(feature = "gzip"))]
+	#[test]
+	#[should_panic(expected = "`compress:gz` requires the `gzip` feature of v_fixtures")]
+	fn test_compression_without_feature_is_rejected() {
+		Fixture::parse("//- /app.log.gz compress:gz\nlog\n");
+	}
+
+	#[cfg(all(feature = "json", feature = "toml"))]
+	#[test]
+	fn test_render_canonicalize_structured() {
+		let fixture = Fixture::parse("//- /a.json\n{\"b\": 1, \"a\": 2}\n//- /b.toml\nz = 1\ny = 2\n//- /c.json\nnot json\n");
+		assert_eq!(
+			FixtureRenderer::new(&fixture).canonicalize_structured().render(),
+			"//- /a.json\n{\n  \"a\": 2,\n  \"b\": 1\n}\n//- /b.toml\ny = 2\nz = 1\n//- /c.json\nnot json\n"
+		);
+	}
+
+	#[cfg(feature = "gzip")]
+	#[test]
+	fn test_compressed_file_roundtrip() {
+		let fixture = Fixture::parse(
+			r#"
+//- /logs/app.log.gz compress:gzip
+started
+stopped
+//- /readme.txt
+hello
+"#,
+		);
+		let temp = fixture.write_to_tempdir();
+
+		let raw = fs::read(temp.path("/logs/app.log.gz")).unwrap();
+		assert_eq!(Compression::detect(&raw), Some(Compression::Gzip));
+		assert_eq!(temp.read("/logs/app.log.gz"), "started\nstopped\n");
+
+		let result = temp.read_all_from_disk();
+		assert_eq!(result, fixture);
+		assert!(result.render().contains("//- /logs/app.log.gz compress:gzip\nstarted\nstopped\n"));
+	}
+
+	#[test]
+	fn test_root_placeholders_roundtrip() {
+		let fixture = Fixture::parse(
+			r#"
+//- /app/config.toml
+data_dir = "{{ROOT}}/data"
+log = "{{CWD}}/app.log"
+//- /data/readme.txt
+plain
+"#,
+		);
+		let temp = fixture.write_to_tempdir();
+		let root = temp.root.to_string_lossy().into_owned();
+		let config = temp.path("/app/config.toml");
+		let expanded = format!("data_dir = \"{root}/data\"\nlog = \"{root}/app.log\"\n");
+		assert_eq!(temp.read("/app/config.toml"), expanded);
+		// Without a cwd both placeholders are the same path, captured as `{{ROOT}}`
+		assert_eq!(temp.read_all_from_disk().files[0].text, "data_dir = \"{{ROOT}}/data\"\nlog = \"{{ROOT}}/app.log\"\n");
+
+		// Setting the cwd later only changes how captures collapse paths
+		let temp = temp.cwd("/app");
+		assert_eq!(fs::read_to_string(&config).unwrap(), expanded);
+		// `<root>/app.log` is not below the cwd `<root>/app`
+		assert_eq!(temp.read_all_from_disk().files[0].text, "data_dir = \"{{ROOT}}/data\"\nlog = \"{{ROOT}}/app.log\"\n");
+
+		// Set at write time, `{{CWD}}` expands to it, and captures collapse it back
+		let temp = fixture.write_to_tempdir_with(&TempDirOptions::new().cwd("/app"));
+		let root = temp.root.to_string_lossy().into_owned();
+		assert_eq!(temp.read("/app/config.toml"), format!("data_dir = \"{root}/data\"\nlog = \"{root}/app/app.log\"\n"));
+		assert_eq!(temp.read_all_from_disk().files[0].text, fixture.files[0].text);
+		assert_eq!(temp.render_changes(), "");
+
+		// Opted out, placeholders are written literally
+		let literal = fixture.write_to_tempdir_with(&TempDirOptions::new().without_placeholders());
+		assert_eq!(literal.read_all(), fixture);
+
+		let captured = Fixture::read_from_directory(&temp.root).unwrap();
+		assert_eq!(
+			FixtureRenderer::new(&captured)
+				.regex("toml")
+				.root_placeholder(&temp.root)
+				.cwd_placeholder(temp.path("/app"))
+				.render(),
+			"data_dir = \"{{ROOT}}/data\"\nlog = \"{{CWD}}/app.log\"\n"
+		);
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn test_mode_and_symlink_roundtrip() {
+		let fixture = Fixture::parse(
+			r#"
+//- /bin/run.sh mode:755
+#!/bin/sh
+//- /current symlink:bin/run.sh
+//- /data.txt
+data
+"#,
+		);
+		let temp = fixture.write_to_tempdir();
+		assert!(fs::symlink_metadata(temp.path("/current")).unwrap().file_type().is_symlink());
+		assert_eq!(temp.read("/current"), "#!/bin/sh\n");
+		let options = ReadOptions::new().record_links_and_modes();
+		assert_eq!(temp.read_all_from_disk_with(&options), fixture);
+		assert_eq!(Fixture::read_from_directory_with(&temp.root, &options).unwrap(), fixture);
+		assert_eq!(temp.read_all(), fixture);
+
+		// By default the link is followed and modes are left out, as before
+		let followed = Fixture::parse(
+			"//- /bin/run.sh
+#!/bin/sh
+//- /current
+#!/bin/sh
+//- /data.txt
+data
+",
+		);
+		assert_eq!(Fixture::read_from_directory(&temp.root).unwrap(), followed);
+		assert_eq!(temp.read_all_from_disk(), followed);
+	}
+
+	#[test]
+	fn test_render_tree_and_file_stats() {
+		let fixture = Fixture::parse("//- /src/lib.rs\npub fn lib() {}\n//- /bin/run.sh mode:755\n#!/bin/sh\n");
+		assert_eq!(
+			FixtureRenderer::new(&fixture).tree().file_stats().render(),
+			"\
+.
+├── bin/
+│   └── run.sh (10 bytes, mode 755)
+└── src/
+    └── lib.rs (16 bytes)
+//- /src/lib.rs lines:1 hash:9afe844020de5586
+pub fn lib() {}
+//- /bin/run.sh mode:755 lines:1 hash:2b26cf13f555773b
+#!/bin/sh
+"
+		);
+		// Single files get a header too, and stats tokens are ignored when parsed back
+		let single = Fixture::parse("x\n");
+		let rendered = FixtureRenderer::new(&single).file_stats().render();
+		assert!(rendered.starts_with("//- /main.rs lines:1 hash:"));
+		assert_eq!(Fixture::parse(&rendered), single);
+	}
+
+	#[test]
+	fn test_lossless_edge_cases() {
+		assert_eq!(Fixture::default().render_lossless(), "//-\n");
+		assert_eq!(Fixture::parse("//-\n"), Fixture::default());
+
+		let fixture = Fixture::parse("//- /main.rs\nfn main() {}\n");
+		assert_eq!(fixture.render(), "fn main() {}\n");
+		assert_eq!(fixture.render_lossless(), "//- /main.rs\nfn main() {}\n");
+
+		let text = "//- a\n\\//- b\nx //- c\n";
+		let fixture = Fixture {
+			files: vec![FixtureFile::new("/a\tb \"c\\".to_owned(), text.to_owned()), FixtureFile::new("/empty".to_owned(), String::new())],
+		};
+		let rendered = fixture.render_lossless();
+		assert_eq!(rendered, "//- \"/a\\tb \\\"c\\\\\"\n\\//- a\n\\\\//- b\nx //- c\n//- /empty\n");
+		assert_eq!(Fixture::parse(&rendered), fixture);
+
+		// Link targets with whitespace are quoted, and so are paths without a leading `/`
+		let mut link = FixtureFile::new("relative/link".to_owned(), String::new());
+		link.meta.symlink = Some("../My Documents/notes.txt".to_owned());
+		let fixture = Fixture { files: vec![link] };
+		let rendered = fixture.render_lossless();
+		assert_eq!(rendered, "//- \"relative/link\" symlink:\"../My Documents/notes.txt\"\n");
+		assert_eq!(Fixture::parse(&rendered), fixture);
+
+		// Content merely containing `//-` is a single file
+		assert_eq!(Fixture::parse("let x = 1; //- y\n").files[0].path, "/main.rs");
+	}
+
+	#[test]
+	#[should_panic(expected = "fixture path must start with `/` unless quoted: \"src/main.rs\"")]
+	fn test_unquoted_relative_path_is_rejected() {
+		Fixture::parse("//- src/main.rs\nfn main() {}\n");
+	}
+
+	mod lossless_props {
+		use proptest::prelude::*;
+
+		use super::*;
+
+		fn meta() -> impl Strategy<Value = FileMeta> {
+			let codecs: Vec<_> = [Compression::Gzip, Compression::Zstd].into_iter().filter(|codec| codec.enabled()).collect();
+			let compress = if codecs.is_empty() {
+				Just(None).boxed()
+			} else {
+				prop::option::of(prop::sample::select(codecs)).boxed()
+			};
+			let compare = prop::option::of(prop_oneof![Just(CompareMode::Text), Just(CompareMode::Json), Just(CompareMode::Yaml)].prop_filter("format enabled", |mode| mode.enabled()));
+			let mode = prop::option::of(0u32..0o7777);
+			let symlink = prop::option::of("[a-z/._: \"\\\\\t-]{0,10}");
+			(compress, compare, mode, symlink, any::<bool>(), any::<bool>()).prop_map(|(compress, compare, mode, symlink, optional, ignore_content)| FileMeta {
+				compress,
+				compare,
+				mode,
+				symlink,
+				optional,
+				ignore_content,
+			})
+		}
+
+		fn file() -> impl Strategy<Value = FixtureFile> {
+			// Paths without a leading `/` can only be built in code, and render quoted
+			let path = prop_oneof!["/[a-z \"\\\\\t\n\r/é\u{a0}-]{0,12}", "[a-z \"\\\\\t/:.-]{0,12}"];
+			let text = prop_oneof![any::<String>(), "(//-|\\\\|\n|\r\n|\r| |\t|a|\"|#|=>){0,30}"];
+			(path, text, meta()).prop_map(|(path, text, meta)| FixtureFile { path, text, meta })
+		}
+
+		proptest! {
+			#[test]
+			fn parse_inverts_render_lossless(files in prop::collection::vec(file(), 0..5)) {
+				let fixture = Fixture { files };
+				prop_assert_eq!(Fixture::parse(&fixture.render_lossless()), fixture);
+			}
+		}
+	}
+
 	#[test]
 	fn test_render_single() {
 		let fixture = Fixture {
 			files: vec![FixtureFile {
 				path: "/main.rs".to_owned(),
 				text: "fn main() {}\n".to_owned(),
+				..Default::default()
 			}],
 		};
 		let rendered = fixture.render();
@@ -775,10 +2210,12 @@ mod tests {
 				FixtureFile {
 					path: "/main.rs".to_owned(),
 					text: "fn main() {}\n".to_owned(),
+					..Default::default()
 				},
 				FixtureFile {
 					path: "/lib.rs".to_owned(),
 					text: "pub fn lib() {}\n".to_owned(),
+					..Default::default()
 				},
 			],
 		};
@@ -904,6 +2341,44 @@ cache
 		assert!(result.contains("/cache/file.txt"));
 	}
 
+	#[test]
+	fn test_render_changes_ignores_cwd_and_unchanged_files() {
+		let fixture = Fixture::parse("//- /app/src/lib.rs\npub fn a() {}\npub fn b() {}\npub fn c() {}\n//- /app/README.md\n# App\n");
+		let temp = fixture.write_to_tempdir().cwd("app");
+		assert_eq!(temp.render_changes(), "");
+
+		fs::remove_file(temp.path("/app/src/lib.rs")).unwrap();
+		temp.write("/app/src/util.rs", "pub fn a() {}\npub fn b() {}\npub fn c() {}\npub fn d() {}");
+		assert_eq!(
+			temp.render_changes(),
+			"//- /app/src/util.rs (renamed from /app/src/lib.rs)\npub fn a() {}\npub fn b() {}\npub fn c() {}\npub fn d() {}\n"
+		);
+		assert!(temp.render_changes_as_diff().contains("rename from app/src/lib.rs\nrename to app/src/util.rs\n"));
+	}
+
+	#[test]
+	fn test_changes_normalize_placeholders() {
+		let fixture = Fixture::parse("//- /app/config.toml\nlog = \"{{ROOT}}/app/x.log\"\ncache = \"{{CWD}}/cache\"\n");
+		// Both sides collapse the same way, whether `{{CWD}}` is the root or `/app`
+		assert_eq!(fixture.write_to_tempdir().render_changes(), "");
+		assert_eq!(fixture.write_to_tempdir().cwd("app").render_changes(), "");
+		let temp = fixture.write_to_tempdir_with(&TempDirOptions::new().cwd("app"));
+		assert_eq!(temp.render_changes(), "");
+
+		temp.write("/app/config.toml", &format!("log = \"{}/x.log\"\n", temp.path("/app").display()));
+		assert_eq!(temp.render_changes(), "//- /app/config.toml (modified)\nlog = \"{{CWD}}/x.log\"\n");
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_changes_keep_symlinks_and_modes() {
+		let temp = Fixture::parse("//- /bin/run.sh mode:755\n#!/bin/sh\n//- /current symlink:bin/run.sh\n").write_to_tempdir();
+		assert_eq!(temp.render_changes(), "");
+
+		temp.write("/bin/run.sh", "#!/bin/bash\n");
+		assert_eq!(temp.render_changes(), "//- /bin/run.sh (modified)\n#!/bin/bash\n");
+	}
+
 	#[test]
 	fn test_read_from_directory() {
 		// Create a temp directory with some files
@@ -933,6 +2408,22 @@ pub fn lib() {}
 		assert!(result.is_none());
 	}
 
+	#[test]
+	fn test_path_filter_shared_by_capture_and_render() {
+		let fixture = Fixture::parse("//- /src/a.rs\na\n//- /src/gen/b.rs\nb\n//- /target/c.rs\nc\n//- /notes.md\nn\n");
+		let filter = PathFilter::from_globs(["*.rs", "!src/gen/", "!/target"]);
+		let temp = fixture.write_to_tempdir();
+
+		let options = ReadOptions::new().filter(filter.clone());
+		let captured = temp.read_all_from_disk_with(&options);
+		assert_eq!(captured.files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(), ["/src/a.rs"]);
+		assert_eq!(Fixture::read_from_directory_with(&temp.root, &options).unwrap(), captured);
+		assert_eq!(
+			FixtureRenderer::new(&fixture).filter(filter).always_show_filepath().render(),
+			FixtureRenderer::new(&captured).always_show_filepath().render()
+		);
+	}
+
 	#[test]
 	fn test_read_from_directory_skips_git() {
 		let fixture = Fixture::parse(
@@ -986,6 +2477,53 @@ other
 		assert!(rendered.contains("\"value\": 42"));
 	}
 
+	#[test]
+	fn test_fixture_renderer_redact_file_lines() {
+		let fixture = Fixture::parse(
+			r#"
+//- /a.txt
+one
+//- /b.log
+started
+pid 4242
+done
+"#,
+		);
+		let redact = |renderer: FixtureRenderer| renderer.redact_file_lines("/b.log", &[2]).redact_message("[PID]").render();
+
+		assert_eq!(redact(FixtureRenderer::new(&fixture)), "//- /a.txt\none\n//- /b.log\nstarted\n[PID]\ndone\n");
+		// Same lines whether or not other files or headers are rendered
+		assert_eq!(redact(FixtureRenderer::new(&fixture).regex("log")), "started\n[PID]\ndone\n");
+		assert_eq!(redact(FixtureRenderer::new(&fixture).regex("log").always_show_filepath()), "//- /b.log\nstarted\n[PID]\ndone\n");
+	}
+
+	#[test]
+	fn test_fixture_renderer_transform_runs_before_redaction() {
+		let fixture = Fixture::parse("//- /a.txt\nid=1\n//- /b.txt\nid=2\n");
+		let suffix = String::from("!");
+		let rendered = FixtureRenderer::new(&fixture)
+			.transform("a", |text| text.replace("id=1", "id=1 seen"))
+			.transform("", |text| text.replace('\n', &format!("{suffix}\n")))
+			.redact_regex(r"id=\d", "id=[N]")
+			.render();
+		assert_eq!(rendered, "//- /a.txt\nid=[N] seen!\n//- /b.txt\nid=[N]!\n");
+	}
+
+	#[test]
+	fn test_fixture_renderer_terminal_normalizations() {
+		let fixture = Fixture::parse("//- /build.log\n\x1b[33mbuilding\x1b[0m   \r\x1b[K\x1b[32mbuilt\x1b[0m  \nok\t\n");
+		assert_eq!(FixtureRenderer::new(&fixture).strip_ansi().render(), "building   \rbuilt  \nok\t\n");
+		assert_eq!(FixtureRenderer::new(&fixture).resolve_carriage_returns().render(), "\x1b[32mbuilt\x1b[0m  \nok\t\n");
+		assert_eq!(
+			FixtureRenderer::new(&fixture).trim_trailing_whitespace().render(),
+			"\x1b[33mbuilding\x1b[0m   \r\x1b[K\x1b[32mbuilt\x1b[0m\nok\n"
+		);
+		assert_eq!(
+			FixtureRenderer::new(&fixture).resolve_carriage_returns().strip_ansi().trim_trailing_whitespace().render(),
+			"built\nok\n"
+		);
+	}
+
 	#[test]
 	fn test_fixture_renderer_custom_redact_message() {
 		let fixture = Fixture::parse(
@@ -1004,6 +2542,32 @@ line 3
 		assert!(!rendered.contains("line 2"));
 	}
 
+	#[test]
+	fn test_fixture_renderer_redact_regex() {
+		let fixture = Fixture::parse(
+			r#"
+//- /logs/run.log
+[2026-01-22 12:00:00] worker 17 started
+[2026-01-22 12:00:03] worker 17 done
+//- /src/main.rs
+// built 2026-01-22
+"#,
+		);
+
+		let rendered = FixtureRenderer::new(&fixture)
+			.redact_regex_in("^/logs/", r"\[(\d{4})-\d{2}-\d{2} [\d:]+\]", "[$1-..]")
+			.redact_regex_in("!^/src/", r"worker \d+", "worker [ID]")
+			.render();
+		assert_eq!(
+			rendered,
+			"//- /logs/run.log\n[2026-..] worker [ID] started\n[2026-..] worker [ID] done\n//- /src/main.rs\n// built 2026-01-22\n"
+		);
+
+		// Follows the content rather than line numbers in the output
+		let rendered = FixtureRenderer::new(&fixture).regex("main").redact_regex(r"\d{4}-\d{2}-\d{2}", "[DATE]").render();
+		assert_eq!(rendered, "// built [DATE]\n");
+	}
+
 	#[test]
 	fn test_fixture_renderer_normalize_git_hashes() {
 		let fixture = Fixture::parse(
@@ -0,0 +1,382 @@
+//! Options for capturing directories, and size limits for captured and rendered file contents.
+//!
+//! A tool under test may write huge files into the fixture directory. A
+//! [`SizeLimit`] caps them at a number of lines or bytes, keeping the head and
+//! tail around a `[... N lines elided ...]` marker, or replacing the content
+//! with a one-line summary. Limits apply on capture through [`ReadOptions`] and
+//! when rendering through [`FixtureRenderer::limit`](crate::FixtureRenderer::limit).
+
+use std::{
+	borrow::Cow,
+	collections::VecDeque,
+	fs,
+	io::{BufRead as _, BufReader, Read as _, Seek as _, SeekFrom},
+	path::Path,
+};
+
+use crate::{
+	Compression, PathFilter,
+	tree::{FNV1A64_OFFSET, fnv1a64, fnv1a64_extend},
+};
+
+/// A cap on a file's size, in lines or bytes.
+///
+/// Content over the cap keeps its first and last halves (snapped to line
+/// boundaries for byte caps) with an elision marker in between, or with
+/// [`summary`](Self::summary) becomes `[N lines, M bytes, hash H]`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SizeLimit {
+	max: usize,
+	unit: Unit,
+	summary: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Unit {
+	Lines,
+	Bytes,
+}
+
+impl SizeLimit {
+	/// Cap content at `max` lines.
+	///
+	/// On capture, an oversized uncompressed file is streamed, holding only the lines it keeps.
+	pub fn lines(max: usize) -> Self {
+		Self {
+			max,
+			unit: Unit::Lines,
+			summary: false,
+		}
+	}
+
+	/// Cap content at `max` bytes.
+	///
+	/// On capture, only the head and tail of an oversized uncompressed file are read.
+	pub fn bytes(max: usize) -> Self {
+		Self {
+			max,
+			unit: Unit::Bytes,
+			summary: false,
+		}
+	}
+
+	/// Replace oversized content with a summary of its size and hash instead.
+	pub fn summary(mut self) -> Self {
+		self.summary = true;
+		self
+	}
+
+	/// Apply the limit to `text`, borrowing it unchanged if it's within the cap.
+	pub fn apply<'t>(&self, text: &'t str) -> Cow<'t, str> {
+		let over = match self.unit {
+			Unit::Lines => text.lines().count() > self.max,
+			Unit::Bytes => text.len() > self.max,
+		};
+		if !over {
+			return Cow::Borrowed(text);
+		}
+		if self.summary {
+			return Cow::Owned(summarize(text));
+		}
+		Cow::Owned(match self.unit {
+			Unit::Lines => {
+				let lines: Vec<&str> = text.split_inclusive('\n').collect();
+				let (head, tail) = (self.max.div_ceil(2), self.max / 2);
+				let mut out: String = lines[..head].concat();
+				out.push_str(&format!("[... {} lines elided ...]\n", lines.len() - head - tail));
+				out.push_str(&lines[lines.len() - tail..].concat());
+				out
+			}
+			Unit::Bytes => {
+				let head_end = floor_char_boundary(text, self.max.div_ceil(2));
+				let tail_start = ceil_char_boundary(text, text.len() - self.max / 2);
+				elide_bytes(&text[..head_end], &text[tail_start..], tail_start - head_end)
+			}
+		})
+	}
+
+	/// Read an oversized plain-text file without holding all of it in memory.
+	///
+	/// A byte cap reads the head and tail only; a line cap streams the file,
+	/// keeping its head lines and a ring of its last lines; a summary counts and
+	/// hashes it while streaming. Returns `None` when the whole file has to be
+	/// read: it fits, it's compressed or it isn't text.
+	pub(crate) fn read_partial(&self, path: &Path) -> Option<String> {
+		let file = fs::File::open(path).ok()?;
+		if self.unit == Unit::Bytes {
+			let len = usize::try_from(file.metadata().ok()?.len()).ok()?;
+			if len <= self.max {
+				return None;
+			}
+			if !self.summary {
+				return self.read_head_and_tail(file, len);
+			}
+		}
+
+		let mut reader = BufReader::new(file);
+		if Compression::detect(reader.fill_buf().ok()?).is_some() {
+			return None;
+		}
+		let (head_len, tail_len) = (self.max.div_ceil(2), self.max / 2);
+		let (mut head, mut tail) = (String::new(), VecDeque::with_capacity(tail_len));
+		let (mut lines, mut bytes, mut hash) = (0, 0, FNV1A64_OFFSET);
+		let mut line = Vec::new();
+		loop {
+			line.clear();
+			if reader.read_until(b'\n', &mut line).ok()? == 0 {
+				break;
+			}
+			// Lines never split a char, so this checks the whole file is text
+			let text = std::str::from_utf8(&line).ok()?;
+			(lines, bytes, hash) = (lines + 1, bytes + line.len(), fnv1a64_extend(hash, &line));
+			if self.summary {
+				continue;
+			}
+			if lines <= head_len {
+				head.push_str(text);
+			} else if tail_len > 0 {
+				if tail.len() == tail_len {
+					tail.pop_front();
+				}
+				tail.push_back(text.to_owned());
+			}
+		}
+		if self.unit == Unit::Lines && lines <= self.max {
+			return None;
+		}
+		if self.summary {
+			return Some(summary_line(lines, bytes, hash));
+		}
+		head.push_str(&format!("[... {} lines elided ...]\n", lines - head_len - tail_len));
+		head.extend(tail);
+		Some(head)
+	}
+
+	/// Read the head and tail of a plain-text file of `len` bytes, over a byte cap.
+	fn read_head_and_tail(&self, mut file: fs::File, len: usize) -> Option<String> {
+		// A few extra bytes on each side to find char boundaries
+		let (head_len, tail_len) = (self.max.div_ceil(2) + 3, self.max / 2 + 3);
+		let mut head = vec![0; head_len.min(len)];
+		file.read_exact(&mut head).ok()?;
+		if Compression::detect(&head).is_some() {
+			return None;
+		}
+		let mut tail = vec![0; tail_len.min(len)];
+		file.seek(SeekFrom::End(-(tail.len() as i64))).ok()?;
+		file.read_exact(&mut tail).ok()?;
+
+		let head = valid_prefix(&head)?;
+		let tail = valid_suffix(&tail)?;
+		let head = &head[..floor_char_boundary(head, self.max.div_ceil(2))];
+		let tail = &tail[ceil_char_boundary(tail, tail.len().saturating_sub(self.max / 2))..];
+		Some(elide_bytes(head, tail, len - head.len() - tail.len()))
+	}
+}
+
+/// Options for reading a directory into a fixture, see
+/// [`Fixture::read_from_directory_with`](crate::Fixture::read_from_directory_with).
+///
+/// # Example
+///
+/// ```
+/// use v_fixtures::{Fixture, ReadOptions, SizeLimit};
+///
+/// let temp = Fixture::parse("//- /big.log\n1\n2\n3\n4\n5\n//- /keep.log\n1\n2\n3\n").write_to_tempdir();
+/// let options = ReadOptions::new().limit(SizeLimit::lines(2)).unlimited_for("keep.log");
+/// let fixture = temp.read_all_from_disk_with(&options);
+/// assert_eq!(fixture.files[0].text, "1\n[... 3 lines elided ...]\n5\n");
+/// assert_eq!(fixture.files[1].text, "1\n2\n3\n");
+/// ```
+#[derive(Default)]
+pub struct ReadOptions {
+	pub(crate) limits: Limits,
+	pub(crate) filter: PathFilter,
+	pub(crate) links_and_modes: bool,
+}
+
+impl ReadOptions {
+	/// Options that read every file in full.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Only read files passing `filter`; others are left out of the fixture.
+	///
+	/// The same filter can be passed to [`FixtureRenderer::filter`](crate::FixtureRenderer::filter).
+	pub fn filter(mut self, filter: PathFilter) -> Self {
+		self.filter = filter;
+		self
+	}
+
+	/// Cap every file's content at `limit`.
+	pub fn limit(mut self, limit: SizeLimit) -> Self {
+		self.limits.default = Some(limit);
+		self
+	}
+
+	/// Record symlinks (`symlink:target`) instead of following them, and the
+	/// modes of executable files (`mode:755`), in each file's [`FileMeta`](crate::FileMeta).
+	///
+	/// By default symlinks to files are read as the files they point to, and modes are left out.
+	pub fn record_links_and_modes(mut self) -> Self {
+		self.links_and_modes = true;
+		self
+	}
+
+	/// Use `limit` for files matching `glob` instead.
+	///
+	/// Globs work like in [`PathFilter`]; when several match, the last one added wins.
+	pub fn limit_for(mut self, glob: &str, limit: SizeLimit) -> Self {
+		self.limits.overrides.push((PathFilter::new().include(glob), Some(limit)));
+		self
+	}
+
+	/// Read files matching `glob` in full.
+	pub fn unlimited_for(mut self, glob: &str) -> Self {
+		self.limits.overrides.push((PathFilter::new().include(glob), None));
+		self
+	}
+}
+
+/// A default size limit with per-path overrides.
+#[derive(Default)]
+pub(crate) struct Limits {
+	pub(crate) default: Option<SizeLimit>,
+	pub(crate) overrides: Vec<(PathFilter, Option<SizeLimit>)>,
+}
+
+impl Limits {
+	/// The limit for the file at `path`, if any.
+	pub(crate) fn for_path(&self, path: &str) -> Option<SizeLimit> {
+		match self.overrides.iter().rev().find(|(pattern, _)| pattern.matches(path)) {
+			Some((_, limit)) => *limit,
+			None => self.default,
+		}
+	}
+}
+
+fn summarize(text: &str) -> String {
+	summary_line(text.lines().count(), text.len(), fnv1a64(text.as_bytes()))
+}
+
+fn summary_line(lines: usize, bytes: usize, hash: u64) -> String {
+	format!("[{lines} lines, {bytes} bytes, hash {hash:016x}]\n")
+}
+
+/// Join `head` and `tail` around a marker for the `elided` bytes between them,
+/// moving the cut points to line boundaries when the halves contain any.
+fn elide_bytes(head: &str, tail: &str, elided: usize) -> String {
+	let head_cut = head.rfind('\n').map_or(head.len(), |idx| idx + 1);
+	let tail_cut = tail.find('\n').map_or(0, |idx| idx + 1);
+	let (head, tail, elided) = (&head[..head_cut], &tail[tail_cut..], elided + (head.len() - head_cut) + tail_cut);
+	let mut out = head.to_owned();
+	if !out.is_empty() && !out.ends_with('\n') {
+		out.push('\n');
+	}
+	out.push_str(&format!("[... {elided} bytes elided ...]\n"));
+	out.push_str(tail);
+	out
+}
+
+fn floor_char_boundary(text: &str, mut idx: usize) -> usize {
+	while !text.is_char_boundary(idx) {
+		idx -= 1;
+	}
+	idx
+}
+
+fn ceil_char_boundary(text: &str, mut idx: usize) -> usize {
+	while !text.is_char_boundary(idx) {
+		idx += 1;
+	}
+	idx
+}
+
+/// The longest valid UTF-8 prefix of `bytes`, or `None` if they're not text.
+fn valid_prefix(bytes: &[u8]) -> Option<&str> {
+	match std::str::from_utf8(bytes) {
+		Ok(text) => Some(text),
+		// Only a truncated char at the end is acceptable
+		Err(e) if e.error_len().is_none() => Some(std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap()),
+		Err(_) => None,
+	}
+}
+
+/// The longest valid UTF-8 suffix of `bytes`, or `None` if they're not text.
+fn valid_suffix(bytes: &[u8]) -> Option<&str> {
+	// Skip continuation bytes of a char cut off at the start
+	let start = bytes.iter().take(3).take_while(|&&b| b & 0xc0 == 0x80).count();
+	std::str::from_utf8(&bytes[start..]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_overrides_match_globs() {
+		let options = ReadOptions::new()
+			.limit(SizeLimit::lines(1))
+			.unlimited_for("*.log")
+			.limit_for("/logs/big.log", SizeLimit::bytes(8));
+		assert_eq!(options.limits.for_path("/out/run.log"), None);
+		assert_eq!(options.limits.for_path("/logs/big.log"), Some(SizeLimit::bytes(8)));
+		// `.` is literal and the glob covers the whole name, unlike a regex
+		assert_eq!(options.limits.for_path("/catalog.txt"), Some(SizeLimit::lines(1)));
+	}
+
+	#[test]
+	fn test_line_limit() {
+		let text = "1\n2\n3\n4\n5\n6\n";
+		assert_eq!(SizeLimit::lines(6).apply(text), text);
+		assert_eq!(SizeLimit::lines(3).apply(text), "1\n2\n[... 3 lines elided ...]\n6\n");
+		assert_eq!(SizeLimit::lines(0).apply(text), "[... 6 lines elided ...]\n");
+		assert_eq!(SizeLimit::lines(2).summary().apply(text), "[6 lines, 12 bytes, hash e8e9c048ed314aaa]\n");
+	}
+
+	#[test]
+	fn test_byte_limit_snaps_to_lines() {
+		let text = "aaaa\nbbbb\ncccc\ndddd\n";
+		assert_eq!(SizeLimit::bytes(12).apply(text), "aaaa\n[... 10 bytes elided ...]\ndddd\n");
+		// No newlines to snap to: cut at char boundaries
+		assert_eq!(SizeLimit::bytes(4).apply("ééééé"), "é\n[... 6 bytes elided ...]\né");
+	}
+
+	#[test]
+	fn test_read_partial_matches_apply() {
+		let text: String = (0..1000).map(|i| format!("line {i} ü\n")).collect();
+		let temp = crate::Fixture::parse(&format!("//- /big.log\n{text}")).write_to_tempdir();
+		let limit = SizeLimit::bytes(100);
+		assert_eq!(limit.read_partial(&temp.path("/big.log")).unwrap(), limit.apply(&text));
+		for limit in [
+			SizeLimit::lines(100),
+			SizeLimit::lines(101),
+			SizeLimit::lines(0),
+			SizeLimit::lines(10).summary(),
+			SizeLimit::bytes(100).summary(),
+		] {
+			assert_eq!(limit.read_partial(&temp.path("/big.log")).unwrap(), limit.apply(&text), "{limit:?}");
+		}
+		// Files within the cap are read as a whole
+		assert_eq!(SizeLimit::lines(1000).read_partial(&temp.path("/big.log")), None);
+	}
+
+	#[test]
+	fn test_line_limit_streams_the_file() {
+		use std::io::Write as _;
+
+		let temp = crate::Fixture::parse("//- /big.log\nfirst\n").write_to_tempdir();
+		let path = temp.path("/big.log");
+		let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+		for i in 0..100_000 {
+			writeln!(file, "line {i}").unwrap();
+		}
+		drop(file);
+
+		// Streamed, keeping only the head and tail lines, instead of falling back to a whole-file read
+		assert_eq!(
+			SizeLimit::lines(4).read_partial(&path).unwrap(),
+			"first\nline 0\n[... 99997 lines elided ...]\nline 99998\nline 99999\n"
+		);
+	}
+}
@@ -0,0 +1,177 @@
+//! Markdown rendering and parsing of fixtures.
+//!
+//! Each file becomes a heading with its path in backticks (followed by any meta
+//! tokens), then a fenced code block tagged with a language inferred from the
+//! extension. This renders well in issues and docs, and
+//! [`Fixture::parse_markdown`] reads it back.
+
+use crate::{FileMeta, Fixture, FixtureFile, parse_meta_tokens};
+
+/// Append `text` as a fenced code block tagged with `language`.
+pub(crate) fn push_code_block(out: &mut String, language: &str, text: &str) {
+	// The fence must be longer than any backtick run in the content
+	let longest_run = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+	let fence = "`".repeat(longest_run.max(2) + 1);
+	out.push_str(&format!("{fence}{language}\n{text}"));
+	if !text.is_empty() && !text.ends_with('\n') {
+		out.push('\n');
+	}
+	out.push_str(&fence);
+	out.push('\n');
+}
+
+/// Append a file section: a heading with the path and meta tokens, then its content.
+pub(crate) fn push_file(out: &mut String, path: &str, meta_tokens: &str, text: &str) {
+	if !out.is_empty() {
+		out.push('\n');
+	}
+	out.push_str(&format!("### `{path}`{meta_tokens}\n\n"));
+	push_code_block(out, language_for_path(path), text);
+}
+
+/// Code block language for a file, from its name or extension (empty if unknown).
+fn language_for_path(path: &str) -> &'static str {
+	let name = path.rsplit('/').next().unwrap_or(path);
+	match name {
+		"Dockerfile" => return "dockerfile",
+		"Makefile" => return "makefile",
+		_ => {}
+	}
+	let Some((_, extension)) = name.rsplit_once('.') else { return "" };
+	match extension {
+		"rs" => "rust",
+		"toml" | "lock" => "toml",
+		"json" => "json",
+		"yaml" | "yml" => "yaml",
+		"md" => "markdown",
+		"py" => "python",
+		"js" | "mjs" | "cjs" => "javascript",
+		"ts" => "typescript",
+		"sh" | "bash" => "sh",
+		"html" | "htm" => "html",
+		"css" => "css",
+		"c" | "h" => "c",
+		"cpp" | "cc" | "hpp" => "cpp",
+		"go" => "go",
+		"java" => "java",
+		"nix" => "nix",
+		"lua" => "lua",
+		"sql" => "sql",
+		"xml" => "xml",
+		"diff" | "patch" => "diff",
+		"txt" | "log" => "text",
+		_ => "",
+	}
+}
+
+impl Fixture {
+	/// Parse Markdown as rendered by [`FixtureRenderer::markdown`](crate::FixtureRenderer::markdown).
+	///
+	/// A heading whose text starts with a backticked path (optionally followed by
+	/// meta tokens) names the file; the next fenced code block is its content.
+	/// Everything else, including code blocks without such a heading, is ignored.
+	///
+	/// # Example
+	///
+	/// ````
+	/// use v_fixtures::Fixture;
+	///
+	/// let fixture = Fixture::parse_markdown(r#"
+	/// State before the fix:
+	///
+	/// ### `/src/main.rs`
+	///
+	/// ```rust
+	/// fn main() {}
+	/// ```
+	/// "#);
+	/// assert_eq!(fixture.files[0].path, "/src/main.rs");
+	/// assert_eq!(fixture.files[0].text, "fn main() {}\n");
+	/// ````
+	pub fn parse_markdown(markdown: &str) -> Self {
+		let mut files = Vec::new();
+		let mut pending: Option<(String, FileMeta)> = None;
+		let mut lines = markdown.lines();
+
+		while let Some(line) = lines.next() {
+			if let Some(heading) = parse_heading(line) {
+				pending = heading;
+				continue;
+			}
+			let Some(fence) = opening_fence(line) else { continue };
+			let mut text = String::new();
+			for line in lines.by_ref() {
+				let trimmed = line.trim_start();
+				if trimmed.starts_with(fence) && trimmed.trim_end().chars().all(|c| fence.starts_with(c)) {
+					break;
+				}
+				text.push_str(line);
+				text.push('\n');
+			}
+			if let Some((path, meta)) = pending.take() {
+				files.push(FixtureFile { path, text, meta });
+			}
+		}
+		Self { files }
+	}
+}
+
+/// `Some(Some(..))` for a file heading, `Some(None)` for any other heading.
+fn parse_heading(line: &str) -> Option<Option<(String, FileMeta)>> {
+	let rest = line.trim_start().strip_prefix('#')?.trim_start_matches('#');
+	let rest = rest.strip_prefix(' ')?.trim();
+	let Some(rest) = rest.strip_prefix('`') else { return Some(None) };
+	let Some((path, tokens)) = rest.split_once('`') else { return Some(None) };
+	Some(Some((path.to_owned(), FileMeta::parse(parse_meta_tokens(tokens).iter().map(String::as_str)))))
+}
+
+/// The fence (three or more backticks or tildes) opening a code block on `line`.
+fn opening_fence(line: &str) -> Option<&str> {
+	let trimmed = line.trim_start();
+	let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+	let len = trimmed.len() - trimmed.trim_start_matches(marker).len();
+	(len >= 3).then(|| &trimmed[..len])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::FixtureRenderer;
+
+	#[test]
+	fn test_markdown_roundtrip() {
+		let fixture = Fixture::parse(
+			r#"
+//- /README.md
+Usage:
+```sh
+run
+```
+//- /bin/run.sh mode:755
+#!/bin/sh
+//- /data
+raw
+"#,
+		);
+		let markdown = FixtureRenderer::new(&fixture).markdown().render();
+		assert_eq!(
+			markdown,
+			"### `/README.md`\n\n````markdown\nUsage:\n```sh\nrun\n```\n````\n\n### `/bin/run.sh` mode:755\n\n```sh\n#!/bin/sh\n```\n\n### `/data`\n\n```\nraw\n```\n"
+		);
+		assert_eq!(Fixture::parse_markdown(&markdown), fixture);
+	}
+
+	#[test]
+	fn test_markdown_roundtrip_quoted_meta() {
+		let fixture = Fixture::parse("//- /link symlink:\"My Docs\"\n");
+		let markdown = FixtureRenderer::new(&fixture).markdown().render();
+		assert_eq!(Fixture::parse_markdown(&markdown), fixture);
+		assert_eq!(fixture.files[0].meta.symlink.as_deref(), Some("My Docs"));
+	}
+
+	#[test]
+	fn test_parse_markdown_ignores_other_blocks() {
+		let fixture = Fixture::parse_markdown("# Report\n\n```\nnot a file\n```\n\n## `/a.txt`\n\nSome prose.\n\n~~~\na\n~~~\n");
+		assert_eq!(fixture, Fixture::parse("//- /a.txt\na\n"));
+	}
+}
@@ -0,0 +1,257 @@
+//! Matching actual fixtures against expected patterns with wildcards.
+//!
+//! See [`assert_fixture_matches`] for the pattern syntax.
+
+use std::fmt;
+
+use crate::Fixture;
+
+/// Inline wildcard matching any characters within a line.
+const INLINE_WILDCARD: &str = "[..]";
+/// Line matching any number of lines.
+const ELISION_LINE: &str = "...";
+
+/// A place where an actual fixture doesn't match an expected pattern.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PatternMismatch {
+	/// A non-optional expected file is absent.
+	MissingFile { path: String },
+	/// The actual fixture has a file the pattern doesn't mention.
+	ExtraFile { path: String },
+	/// File content stops matching at the given lines (both 1-indexed).
+	///
+	/// `None` for `expected` or `found` means the pattern or the file ended there.
+	Line {
+		path: String,
+		pattern_line: usize,
+		actual_line: usize,
+		expected: Option<String>,
+		found: Option<String>,
+	},
+}
+
+impl fmt::Display for PatternMismatch {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::MissingFile { path } => write!(f, "missing file {path}"),
+			Self::ExtraFile { path } => write!(f, "unexpected file {path}"),
+			Self::Line {
+				path,
+				pattern_line,
+				actual_line,
+				expected,
+				found,
+			} => {
+				write!(f, "{path}:{actual_line} (pattern line {pattern_line}): expected ")?;
+				match expected {
+					Some(expected) => write!(f, "{expected:?}")?,
+					None => write!(f, "end of file")?,
+				}
+				match found {
+					Some(found) => write!(f, ", found {found:?}"),
+					None => write!(f, ", found end of file"),
+				}
+			}
+		}
+	}
+}
+
+impl Fixture {
+	/// Match `actual` against this fixture used as a pattern, returning every mismatch.
+	///
+	/// See [`assert_fixture_matches`] for the pattern syntax. Lines are compared
+	/// without their terminators, so a missing trailing newline is not a mismatch.
+	pub fn match_pattern(&self, actual: &Fixture) -> Vec<PatternMismatch> {
+		let mut mismatches = Vec::new();
+		for expected in &self.files {
+			let Some(actual_file) = actual.file(&expected.path) else {
+				if !expected.meta.optional {
+					mismatches.push(PatternMismatch::MissingFile { path: expected.path.clone() });
+				}
+				continue;
+			};
+			if expected.meta.ignore_content {
+				continue;
+			}
+			let pattern: Vec<&str> = expected.text.split_terminator('\n').collect();
+			let lines: Vec<&str> = actual_file.text.split_terminator('\n').collect();
+			if let Err((p, a)) = match_lines(&pattern, &lines) {
+				mismatches.push(PatternMismatch::Line {
+					path: expected.path.clone(),
+					pattern_line: p + 1,
+					actual_line: a + 1,
+					expected: pattern.get(p).map(|s| (*s).to_owned()),
+					found: lines.get(a).map(|s| (*s).to_owned()),
+				});
+			}
+		}
+		for file in &actual.files {
+			if !self.contains(&file.path) {
+				mismatches.push(PatternMismatch::ExtraFile { path: file.path.clone() });
+			}
+		}
+		mismatches
+	}
+}
+
+/// Assert that `actual` matches the `expected` pattern fixture, panicking with every mismatch.
+///
+/// In the expected fixture:
+/// - `[..]` inside a line matches any run of characters on that line
+/// - a line consisting only of `...` matches any number of lines, including none
+/// - a file marked `optional` may be absent from the actual fixture
+/// - a file marked `ignore-content` must exist, but its content is not checked
+///
+/// # Example
+///
+/// ```
+/// use v_fixtures::{Fixture, assert_fixture_matches};
+///
+/// let expected = Fixture::parse(r#"
+/// //- /out.log
+/// started job [..]
+/// ...
+/// done in [..]ms
+/// //- /id.txt ignore-content
+/// //- /debug.log optional
+/// "#);
+/// let actual = Fixture::parse(r#"
+/// //- /out.log
+/// started job 7f3a
+/// step 1
+/// step 2
+/// done in 42ms
+/// //- /id.txt
+/// 0b5e0d1c
+/// "#);
+/// assert_fixture_matches(&expected, &actual);
+/// ```
+#[track_caller]
+pub fn assert_fixture_matches(expected: &Fixture, actual: &Fixture) {
+	let mismatches = expected.match_pattern(actual);
+	if mismatches.is_empty() {
+		return;
+	}
+	let mut message = "fixture does not match pattern:\n".to_owned();
+	for mismatch in &mismatches {
+		message.push_str(&format!("  {mismatch}\n"));
+	}
+	panic!("{message}");
+}
+
+/// Match lines against a pattern with `...` elisions.
+///
+/// On failure returns the (pattern, actual) indices of the furthest point matching got to.
+fn match_lines(pattern: &[&str], lines: &[&str]) -> Result<(), (usize, usize)> {
+	let (mut p, mut a) = (0, 0);
+	// Last `...` seen: pattern index after it, and the actual index it currently extends to
+	let mut elision: Option<(usize, usize)> = None;
+	let mut furthest = (0, 0);
+
+	loop {
+		if (a, p) > (furthest.1, furthest.0) {
+			furthest = (p, a);
+		}
+		if p < pattern.len() && pattern[p] == ELISION_LINE {
+			elision = Some((p + 1, a));
+			p += 1;
+		} else if p < pattern.len() && a < lines.len() && line_matches(pattern[p], lines[a]) {
+			p += 1;
+			a += 1;
+		} else if p == pattern.len() && a == lines.len() {
+			return Ok(());
+		} else if let Some((after, extent)) = elision
+			&& extent < lines.len()
+		{
+			// Let the elision swallow one more line and retry
+			elision = Some((after, extent + 1));
+			p = after;
+			a = extent + 1;
+		} else {
+			return Err(furthest);
+		}
+	}
+}
+
+/// Match a single line against a pattern line with `[..]` wildcards.
+fn line_matches(pattern: &str, line: &str) -> bool {
+	let mut parts = pattern.split(INLINE_WILDCARD);
+	let first = parts.next().unwrap_or("");
+	let Some(mut rest) = line.strip_prefix(first) else { return false };
+	let parts: Vec<&str> = parts.collect();
+	let Some((last, middle)) = parts.split_last() else {
+		return rest.is_empty();
+	};
+	for part in middle {
+		match rest.find(part) {
+			Some(idx) => rest = &rest[idx + part.len()..],
+			None => return false,
+		}
+	}
+	rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_line_matches() {
+		assert!(line_matches("id: [..]", "id: 1234"));
+		assert!(line_matches("[..]", ""));
+		assert!(line_matches("a[..]b[..]c", "a-b-b-c"));
+		assert!(line_matches("x[..]x", "xx"));
+		assert!(!line_matches("x[..]x", "x"));
+		assert!(!line_matches("exact", "exactly"));
+	}
+
+	#[test]
+	fn test_match_lines_elision() {
+		assert_eq!(match_lines(&["a", "...", "z"], &["a", "b", "c", "z"]), Ok(()));
+		assert_eq!(match_lines(&["a", "...", "z"], &["a", "z"]), Ok(()));
+		assert_eq!(match_lines(&["...", "b", "..."], &["a", "b", "c"]), Ok(()));
+		assert_eq!(match_lines(&["..."], &[]), Ok(()));
+		assert_eq!(match_lines(&["a", "...", "z"], &["a", "b", "c"]), Err((2, 3)));
+		assert_eq!(match_lines(&["a", "b"], &["a", "c"]), Err((1, 1)));
+	}
+
+	#[test]
+	fn test_match_pattern_reports_locations() {
+		let expected = Fixture::parse(
+			r#"
+//- /log.txt
+start [..]
+...
+end
+//- /id.txt ignore-content
+//- /maybe.txt optional
+//- /required.txt
+here
+"#,
+		);
+		let actual = Fixture::parse("//- /log.txt\nstart 1\nstep\nfinish\n//- /id.txt\nabc\n//- /extra.txt\nx\n");
+
+		let mismatches = expected.match_pattern(&actual);
+		assert_eq!(
+			mismatches,
+			[
+				PatternMismatch::Line {
+					path: "/log.txt".to_owned(),
+					pattern_line: 3,
+					actual_line: 4,
+					expected: Some("end".to_owned()),
+					found: None,
+				},
+				PatternMismatch::MissingFile { path: "/required.txt".to_owned() },
+				PatternMismatch::ExtraFile { path: "/extra.txt".to_owned() },
+			]
+		);
+		assert_eq!(mismatches[0].to_string(), "/log.txt:4 (pattern line 3): expected \"end\", found end of file");
+	}
+
+	#[test]
+	#[should_panic(expected = "fixture does not match pattern:\n  /main.rs:1 (pattern line 1)")]
+	fn test_assert_fixture_matches_panics() {
+		assert_fixture_matches(&Fixture::parse("value: [..]ms\n"), &Fixture::parse("value: 42s\n"));
+	}
+}
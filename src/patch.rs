@@ -0,0 +1,557 @@
+//! Applying unified diffs and `git diff` patches to fixtures.
+//!
+//! Supports modification, creation (`--- /dev/null` or `new file mode`),
+//! deletion (`+++ /dev/null` or `deleted file mode`) and renames
+//! (`rename from`/`rename to`, with or without hunks). Hunks are matched
+//! exactly, but may apply at an offset from the line number in their header,
+//! like `git apply` does.
+
+use std::fmt;
+
+use crate::{Fixture, FixtureFile};
+
+/// Error returned by [`Fixture::apply_patch`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PatchError {
+	/// The patch text is malformed. `line` is 1-indexed within the patch.
+	Parse { line: usize, message: String },
+	/// The patch modifies, deletes or renames a file the fixture doesn't have.
+	MissingFile { path: String },
+	/// The patch creates (or renames onto) a file the fixture already has.
+	FileExists { path: String },
+	/// A hunk's context or removed lines don't match the file.
+	///
+	/// `hunk` is 1-indexed within the file's patch, `line` is the 1-indexed
+	/// line in the file where the hunk was expected to apply.
+	HunkMismatch {
+		path: String,
+		hunk: usize,
+		line: usize,
+		expected: String,
+		found: Option<String>,
+	},
+}
+
+impl fmt::Display for PatchError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Parse { line, message } => write!(f, "malformed patch at line {line}: {message}"),
+			Self::MissingFile { path } => write!(f, "patch targets {path}, which is not in the fixture"),
+			Self::FileExists { path } => write!(f, "patch creates {path}, which already exists in the fixture"),
+			Self::HunkMismatch { path, hunk, line, expected, found } => {
+				write!(f, "hunk #{hunk} of {path} does not apply at line {line}: expected {expected:?}, ")?;
+				match found {
+					Some(found) => write!(f, "found {found:?}"),
+					None => write!(f, "found end of file"),
+				}
+			}
+		}
+	}
+}
+
+impl std::error::Error for PatchError {}
+
+impl Fixture {
+	/// Apply a unified diff or `git diff` patch, returning the patched fixture.
+	///
+	/// Paths in the patch are relative to the fixture root; `a/` and `b/`
+	/// prefixes are stripped. Modified and renamed files keep their position,
+	/// created files are appended.
+	///
+	/// # Example
+	///
+	/// ```
+	/// use v_fixtures::Fixture;
+	///
+	/// let before = Fixture::parse(r#"
+	/// //- /src/main.rs
+	/// fn main() {
+	///     println!("hello");
+	/// }
+	/// "#);
+	/// let after = before.apply_patch(r#"
+	/// diff --git a/src/main.rs b/src/main.rs
+	/// --- a/src/main.rs
+	/// +++ b/src/main.rs
+	/// @@ -1,3 +1,3 @@
+	///  fn main() {
+	/// -    println!("hello");
+	/// +    println!("bye");
+	///  }
+	/// "#).unwrap();
+	/// assert!(after.files[0].text.contains("bye"));
+	/// ```
+	pub fn apply_patch(&self, patch: &str) -> Result<Fixture, PatchError> {
+		let mut fixture = self.clone();
+		for file_patch in parse_patch(patch)? {
+			file_patch.apply(&mut fixture)?;
+		}
+		Ok(fixture)
+	}
+}
+
+/// Patch for a single file. `None` paths stand for `/dev/null`.
+#[derive(Debug)]
+struct FilePatch {
+	old_path: Option<String>,
+	new_path: Option<String>,
+	hunks: Vec<Hunk>,
+}
+
+#[derive(Debug)]
+struct Hunk {
+	old_start: usize,
+	lines: Vec<HunkLine>,
+	/// `\ No newline at end of file` applied to the old side
+	old_no_newline: bool,
+	/// `\ No newline at end of file` applied to the new side
+	new_no_newline: bool,
+}
+
+#[derive(Debug)]
+enum HunkLine {
+	Context(String),
+	Remove(String),
+	Add(String),
+}
+
+impl Hunk {
+	fn old_lines(&self) -> Vec<&str> {
+		self.lines
+			.iter()
+			.filter_map(|l| match l {
+				HunkLine::Context(s) | HunkLine::Remove(s) => Some(s.as_str()),
+				HunkLine::Add(_) => None,
+			})
+			.collect()
+	}
+
+	fn new_lines(&self) -> Vec<&str> {
+		self.lines
+			.iter()
+			.filter_map(|l| match l {
+				HunkLine::Context(s) | HunkLine::Add(s) => Some(s.as_str()),
+				HunkLine::Remove(_) => None,
+			})
+			.collect()
+	}
+}
+
+impl FilePatch {
+	fn apply(&self, fixture: &mut Fixture) -> Result<(), PatchError> {
+		match (&self.old_path, &self.new_path) {
+			(None, Some(new)) => {
+				if fixture.contains(new) {
+					return Err(PatchError::FileExists { path: new.clone() });
+				}
+				let text = apply_hunks(new, "", &self.hunks)?;
+				fixture.files.push(FixtureFile::new(new.clone(), text));
+			}
+			(Some(old), None) => {
+				let index = file_index(fixture, old)?;
+				apply_hunks(old, &fixture.files[index].text, &self.hunks)?;
+				fixture.files.remove(index);
+			}
+			(Some(old), Some(new)) => {
+				let index = file_index(fixture, old)?;
+				if old != new && fixture.contains(new) {
+					return Err(PatchError::FileExists { path: new.clone() });
+				}
+				let text = apply_hunks(old, &fixture.files[index].text, &self.hunks)?;
+				let file = &mut fixture.files[index];
+				file.path = new.clone();
+				file.text = text;
+			}
+			(None, None) => unreachable!("file patch without paths is rejected while parsing"),
+		}
+		Ok(())
+	}
+}
+
+fn file_index(fixture: &Fixture, path: &str) -> Result<usize, PatchError> {
+	fixture.files.iter().position(|f| f.path == path).ok_or_else(|| PatchError::MissingFile { path: path.to_owned() })
+}
+
+/// Apply hunks in order, tracking how far earlier hunks shifted the file.
+fn apply_hunks(path: &str, text: &str, hunks: &[Hunk]) -> Result<String, PatchError> {
+	let mut lines: Vec<&str> = text.split_terminator('\n').collect();
+	let mut ends_with_newline = text.is_empty() || text.ends_with('\n');
+	let mut offset: isize = 0;
+
+	for (i, hunk) in hunks.iter().enumerate() {
+		let old = hunk.old_lines();
+		let new = hunk.new_lines();
+
+		// A hunk that removes nothing inserts *after* `old_start`
+		let start = if old.is_empty() { hunk.old_start } else { hunk.old_start.saturating_sub(1) };
+		let expected = (start as isize + offset).clamp(0, lines.len() as isize) as usize;
+
+		let pos = find_hunk(&lines, &old, expected).ok_or_else(|| {
+			let (expected_line, found) = old
+				.iter()
+				.enumerate()
+				.find(|(j, l)| lines.get(expected + j) != Some(*l))
+				.map(|(j, l)| (*l, lines.get(expected + j)))
+				.unwrap_or(("", None));
+			PatchError::HunkMismatch {
+				path: path.to_owned(),
+				hunk: i + 1,
+				line: expected + 1,
+				expected: expected_line.to_owned(),
+				found: found.map(|s| (*s).to_owned()),
+			}
+		})?;
+
+		lines.splice(pos..pos + old.len(), new.iter().copied());
+		offset += new.len() as isize - old.len() as isize + (pos as isize - expected as isize);
+
+		if pos + new.len() == lines.len() {
+			if hunk.new_no_newline {
+				ends_with_newline = false;
+			} else if hunk.old_no_newline {
+				ends_with_newline = true;
+			}
+		}
+	}
+
+	let mut result = lines.join("\n");
+	if ends_with_newline && !lines.is_empty() {
+		result.push('\n');
+	}
+	Ok(result)
+}
+
+/// Find where `old` occurs in `lines`, preferring positions closest to `expected`.
+fn find_hunk(lines: &[&str], old: &[&str], expected: usize) -> Option<usize> {
+	let matches_at = |pos: usize| pos + old.len() <= lines.len() && lines[pos..pos + old.len()] == *old;
+	(0..=lines.len()).find_map(|distance| [expected.checked_sub(distance), Some(expected + distance)].into_iter().flatten().find(|&pos| matches_at(pos)))
+}
+
+fn parse_patch(patch: &str) -> Result<Vec<FilePatch>, PatchError> {
+	let lines: Vec<&str> = patch.split_terminator('\n').collect();
+	let mut patches = Vec::new();
+	let mut i = 0;
+
+	while i < lines.len() {
+		let line = lines[i];
+		let start = i;
+		let mut file_patch = if let Some(rest) = line.strip_prefix("diff --git ") {
+			let (old, new) = parse_git_header_paths(rest).ok_or_else(|| parse_error(i, "cannot parse `diff --git` paths"))?;
+			let mut file_patch = FilePatch {
+				old_path: Some(old),
+				new_path: Some(new),
+				hunks: Vec::new(),
+			};
+			i += 1;
+			while i < lines.len() && !lines[i].starts_with("diff --git ") && !lines[i].starts_with("@@") {
+				let header = lines[i];
+				i += 1;
+				if header.starts_with("new file mode") {
+					file_patch.old_path = None;
+				} else if header.starts_with("deleted file mode") {
+					file_patch.new_path = None;
+				} else if let Some(path) = header.strip_prefix("rename from ") {
+					file_patch.old_path = Some(normalize_path(path));
+				} else if let Some(path) = header.strip_prefix("rename to ") {
+					file_patch.new_path = Some(normalize_path(path));
+				} else if let Some(path) = header.strip_prefix("--- ") {
+					file_patch.old_path = parse_side(path);
+				} else if let Some(path) = header.strip_prefix("+++ ") {
+					file_patch.new_path = parse_side(path);
+					break;
+				} else if header.starts_with("Binary files") || header.starts_with("GIT binary patch") {
+					return Err(parse_error(i - 1, "binary patches are not supported"));
+				}
+			}
+			file_patch
+		} else if let Some(old) = line.strip_prefix("--- ")
+			&& let Some(new) = lines.get(i + 1).and_then(|l| l.strip_prefix("+++ "))
+		{
+			i += 2;
+			FilePatch {
+				old_path: parse_side(old),
+				new_path: parse_side(new),
+				hunks: Vec::new(),
+			}
+		} else {
+			// Preamble (commit message, `index` lines of other tools, ...)
+			i += 1;
+			continue;
+		};
+
+		if file_patch.old_path.is_none() && file_patch.new_path.is_none() {
+			return Err(parse_error(start, "patch has neither an old nor a new path"));
+		}
+		while i < lines.len() && lines[i].starts_with("@@") {
+			let (hunk, next) = parse_hunk(&lines, i)?;
+			file_patch.hunks.push(hunk);
+			i = next;
+		}
+		patches.push(file_patch);
+	}
+
+	Ok(patches)
+}
+
+/// Parse the hunk starting at `lines[start]`, returning it and the index of the following line.
+fn parse_hunk(lines: &[&str], start: usize) -> Result<(Hunk, usize), PatchError> {
+	let (old_start, mut old_remaining, mut new_remaining) = parse_hunk_header(lines[start]).ok_or_else(|| parse_error(start, "malformed hunk header"))?;
+	let mut hunk = Hunk {
+		old_start,
+		lines: Vec::new(),
+		old_no_newline: false,
+		new_no_newline: false,
+	};
+
+	let mut i = start + 1;
+	loop {
+		let Some(line) = lines.get(i) else {
+			if old_remaining > 0 || new_remaining > 0 {
+				return Err(parse_error(i, "unexpected end of patch inside hunk"));
+			}
+			break;
+		};
+		if line.starts_with('\\') {
+			match hunk.lines.last() {
+				Some(HunkLine::Remove(_)) => hunk.old_no_newline = true,
+				Some(HunkLine::Add(_)) => hunk.new_no_newline = true,
+				Some(HunkLine::Context(_)) => {
+					hunk.old_no_newline = true;
+					hunk.new_no_newline = true;
+				}
+				None => return Err(parse_error(i, "`\\ No newline at end of file` before any hunk line")),
+			}
+			i += 1;
+			continue;
+		}
+		if old_remaining == 0 && new_remaining == 0 {
+			break;
+		}
+
+		// Some tools strip the single space of empty context lines
+		let kind = line.chars().next();
+		let content = &line[kind.map_or(0, char::len_utf8)..];
+		let hunk_line = match kind {
+			Some(' ') | None => HunkLine::Context(content.to_owned()),
+			Some('-') => HunkLine::Remove(content.to_owned()),
+			Some('+') => HunkLine::Add(content.to_owned()),
+			Some(_) => return Err(parse_error(i, "expected hunk line starting with ' ', '-' or '+'")),
+		};
+		match hunk_line {
+			HunkLine::Context(_) => {
+				old_remaining = old_remaining.checked_sub(1).ok_or_else(|| parse_error(i, "hunk has more lines than its header declares"))?;
+				new_remaining = new_remaining.checked_sub(1).ok_or_else(|| parse_error(i, "hunk has more lines than its header declares"))?;
+			}
+			HunkLine::Remove(_) => old_remaining = old_remaining.checked_sub(1).ok_or_else(|| parse_error(i, "hunk removes more lines than its header declares"))?,
+			HunkLine::Add(_) => new_remaining = new_remaining.checked_sub(1).ok_or_else(|| parse_error(i, "hunk adds more lines than its header declares"))?,
+		}
+		hunk.lines.push(hunk_line);
+		i += 1;
+	}
+
+	Ok((hunk, i))
+}
+
+/// Parse `@@ -l[,s] +l[,s] @@ ...` into (old_start, old_count, new_count).
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+	let rest = line.strip_prefix("@@ -")?;
+	let (ranges, _) = rest.split_once(" @@")?;
+	let (old, new) = ranges.split_once(" +")?;
+	let parse_range = |range: &str| -> Option<(usize, usize)> {
+		match range.split_once(',') {
+			Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+			None => Some((range.parse().ok()?, 1)),
+		}
+	};
+	let (old_start, old_count) = parse_range(old)?;
+	let (_, new_count) = parse_range(new)?;
+	Some((old_start, old_count, new_count))
+}
+
+/// Parse the paths of a `diff --git a/<old> b/<new>` header.
+fn parse_git_header_paths(rest: &str) -> Option<(String, String)> {
+	let rest = rest.strip_prefix("a/")?;
+	let (old, new) = rest.split_once(" b/")?;
+	Some((normalize_path(old), normalize_path(new)))
+}
+
+/// Parse the path of a `---`/`+++` line; `None` for `/dev/null`.
+fn parse_side(path: &str) -> Option<String> {
+	// Strip the timestamp that `diff -u` appends after a tab
+	let path = path.split('\t').next().unwrap_or(path).trim_end();
+	if path == "/dev/null" {
+		return None;
+	}
+	let path = path.strip_prefix("a/").or_else(|| path.strip_prefix("b/")).unwrap_or(path);
+	Some(normalize_path(path))
+}
+
+/// Convert a patch path into a fixture path (rooted at `/`).
+fn normalize_path(path: &str) -> String {
+	format!("/{}", path.trim_start_matches('/'))
+}
+
+fn parse_error(index: usize, message: &str) -> PatchError {
+	PatchError::Parse {
+		line: index + 1,
+		message: message.to_owned(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::parse_before_patch;
+
+	fn base() -> Fixture {
+		Fixture::parse(
+			r#"
+//- /src/main.rs
+fn main() {
+    println!("hello");
+}
+//- /src/lib.rs
+pub fn lib() {}
+//- /README.md
+# Project
+"#,
+		)
+	}
+
+	#[test]
+	fn test_apply_modification() {
+		let patched = base()
+			.apply_patch(
+				r#"diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,4 @@
+ fn main() {
+-    println!("hello");
++    println!("bye");
++    std::process::exit(0);
+ }
+"#,
+			)
+			.unwrap();
+		assert_eq!(patched.file("/src/main.rs").unwrap().text, "fn main() {\n    println!(\"bye\");\n    std::process::exit(0);\n}\n");
+		assert_eq!(patched.files.len(), 3);
+	}
+
+	#[test]
+	fn test_apply_create_delete_rename() {
+		let patched = base()
+			.apply_patch(
+				r#"diff --git a/NOTES.md b/NOTES.md
+new file mode 100644
+--- /dev/null
++++ b/NOTES.md
+@@ -0,0 +1,2 @@
++note 1
++note 2
+diff --git a/README.md b/README.md
+deleted file mode 100644
+--- a/README.md
++++ /dev/null
+@@ -1 +0,0 @@
+-# Project
+diff --git a/src/lib.rs b/src/util.rs
+similarity index 100%
+rename from src/lib.rs
+rename to src/util.rs
+"#,
+			)
+			.unwrap();
+
+		let paths: Vec<_> = patched.files.iter().map(|f| f.path.as_str()).collect();
+		assert_eq!(paths, ["/src/main.rs", "/src/util.rs", "/NOTES.md"]);
+		assert_eq!(patched.file("/NOTES.md").unwrap().text, "note 1\nnote 2\n");
+		assert_eq!(patched.file("/src/util.rs").unwrap().text, "pub fn lib() {}\n");
+	}
+
+	#[test]
+	fn test_apply_plain_unified_diff_with_offset() {
+		let fixture = Fixture::parse(
+			r#"
+//- /list.txt
+a
+b
+c
+d
+e
+"#,
+		);
+		// Header claims line 1, but the context is at line 3
+		let patched = fixture
+			.apply_patch("--- list.txt\t2026-01-01 00:00:00\n+++ list.txt\t2026-01-02 00:00:00\n@@ -1,2 +1,2 @@\n c\n-d\n+D\n")
+			.unwrap();
+		assert_eq!(patched.files[0].text, "a\nb\nc\nD\ne\n");
+	}
+
+	#[test]
+	fn test_apply_no_newline_at_end_of_file() {
+		let fixture = Fixture::parse("//- /a.txt\none\ntwo\n");
+		let patched = fixture
+			.apply_patch("--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+three\n\\ No newline at end of file\n")
+			.unwrap();
+		assert_eq!(patched.files[0].text, "one\nthree");
+	}
+
+	#[test]
+	fn test_hunk_mismatch_error() {
+		let err = base()
+			.apply_patch("--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,2 +1,2 @@\n fn main() {\n-    println!(\"goodbye\");\n+    println!(\"bye\");\n")
+			.unwrap_err();
+		assert_eq!(
+			err,
+			PatchError::HunkMismatch {
+				path: "/src/main.rs".to_owned(),
+				hunk: 1,
+				line: 1,
+				expected: "    println!(\"goodbye\");".to_owned(),
+				found: Some("    println!(\"hello\");".to_owned()),
+			}
+		);
+		assert!(err.to_string().starts_with("hunk #1 of /src/main.rs does not apply at line 1"));
+	}
+
+	#[test]
+	fn test_hunk_line_with_multibyte_prefix_is_a_parse_error() {
+		let err = base().apply_patch("--- a/README.md\n+++ b/README.md\n@@ -1 +1 @@\n→# Project\n").unwrap_err();
+		assert!(matches!(err, PatchError::Parse { line: 4, .. }), "{err:?}");
+	}
+
+	#[test]
+	fn test_missing_and_existing_files() {
+		let err = base().apply_patch("--- a/nope.rs\n+++ b/nope.rs\n@@ -1 +1 @@\n-a\n+b\n").unwrap_err();
+		assert_eq!(err, PatchError::MissingFile { path: "/nope.rs".to_owned() });
+
+		let err = base().apply_patch("--- /dev/null\n+++ b/README.md\n@@ -0,0 +1 @@\n+dup\n").unwrap_err();
+		assert_eq!(err, PatchError::FileExists { path: "/README.md".to_owned() });
+	}
+
+	#[test]
+	fn test_parse_before_patch() {
+		let (before, after) = parse_before_patch(
+			r#"
+            //- /config.toml
+            name = "app"
+            version = 1
+
+            debug = false
+            =>
+            --- a/config.toml
+            +++ b/config.toml
+            @@ -1,4 +1,4 @@
+             name = "app"
+            -version = 1
+            +version = 2
+
+             debug = false
+        "#,
+		);
+		assert!(before.files[0].text.contains("version = 1"));
+		assert_eq!(after.files[0].text, before.files[0].text.replace("version = 1", "version = 2"));
+	}
+}
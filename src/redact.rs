@@ -0,0 +1,148 @@
+//! Built-in redaction presets for common non-deterministic values.
+//!
+//! Each preset replaces the values it finds with numbered placeholders like
+//! `[UUID-1]`, numbered in order of first appearance across the whole render,
+//! so equal values get equal placeholders and different ones stay distinguishable.
+
+use std::{borrow::Cow, collections::HashMap, sync::LazyLock};
+
+use regex::{Captures, Regex};
+
+/// A kind of non-deterministic value to redact, see [`FixtureRenderer::redact_preset`](crate::FixtureRenderer::redact_preset).
+///
+/// When several presets are enabled they apply in declaration order, so e.g. the
+/// hex groups of a UUID are never redacted as hashes.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum RedactionPreset {
+	/// A temp directory path such as `/tmp/v_fixture_a1B2`, or the root of any temp fixture created wherever, as `[TEMP-n]`; the path below it is kept
+	TempPath,
+	/// A UUID such as `67e55044-10b1-426f-9247-bb680e5fe0c8`, as `[UUID-n]`
+	Uuid,
+	/// An ISO-8601 date and time such as `2026-01-22T12:00:00Z`, as `[TIMESTAMP-n]`
+	Timestamp,
+	/// Unix seconds or milliseconds such as `1769083200`, as `[EPOCH-n]`
+	UnixEpoch,
+	/// A duration such as `42ms`, `1.5s` or `1m30s`, as `[DURATION-n]`
+	Duration,
+	/// A hex hash of 7 to 64 digits such as `a0f7d74`, as `[HASH-n]`
+	HexHash,
+}
+
+impl RedactionPreset {
+	/// Every preset, in application order.
+	pub const ALL: [Self; 6] = [Self::TempPath, Self::Uuid, Self::Timestamp, Self::UnixEpoch, Self::Duration, Self::HexHash];
+
+	/// Placeholder label, e.g. `UUID` for `[UUID-1]`.
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::TempPath => "TEMP",
+			Self::Uuid => "UUID",
+			Self::Timestamp => "TIMESTAMP",
+			Self::UnixEpoch => "EPOCH",
+			Self::Duration => "DURATION",
+			Self::HexHash => "HASH",
+		}
+	}
+
+	fn regex(self) -> &'static Regex {
+		static UUID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b").unwrap());
+		static TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}(?::\d{2}(?:[.,]\d+)?)?(?:Z|[+-]\d{2}(?::?\d{2})?)?\b").unwrap());
+		static EPOCH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b1\d{9}(?:\d{3})?\b").unwrap());
+		static DURATION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(?:\d+(?:\.\d+)?(?:ns|us|µs|ms|s|m|h))+\b").unwrap());
+		static HEX_HASH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b[0-9a-f]{7,64}\b").unwrap());
+		static TEMP_PATH: LazyLock<Regex> = LazyLock::new(|| {
+			let temp = std::env::temp_dir();
+			let mut roots = vec![temp.to_string_lossy().into_owned()];
+			if let Ok(canonical) = temp.canonicalize() {
+				roots.push(canonical.to_string_lossy().into_owned());
+			}
+			let roots: Vec<String> = roots.iter().map(|r| regex::escape(r.trim_end_matches(['/', '\\']))).collect();
+			Regex::new(&format!(r#"(?:{})[/\\][^/\\\s"'`:;,]+"#, roots.join("|"))).unwrap()
+		});
+
+		match self {
+			Self::TempPath => &TEMP_PATH,
+			Self::Uuid => &UUID,
+			Self::Timestamp => &TIMESTAMP,
+			Self::UnixEpoch => &EPOCH,
+			Self::Duration => &DURATION,
+			Self::HexHash => &HEX_HASH,
+		}
+	}
+
+	/// Whether a regex match really is a value of this kind.
+	fn accepts(self, value: &str) -> bool {
+		match self {
+			// Plain numbers and words are not hashes
+			Self::HexHash => value.bytes().any(|b| b.is_ascii_digit()) && value.bytes().any(|b| b.is_ascii_alphabetic()),
+			_ => true,
+		}
+	}
+}
+
+/// Numbers redacted values consistently over one render.
+#[derive(Default)]
+pub(crate) struct PresetNumbering {
+	numbers: HashMap<(RedactionPreset, String), usize>,
+	counts: HashMap<RedactionPreset, usize>,
+}
+
+impl PresetNumbering {
+	/// Replace every value `preset` finds in `text` with its numbered placeholder.
+	///
+	/// Temp fixture roots are matched first, wherever they were created.
+	pub(crate) fn apply<'t>(&mut self, preset: RedactionPreset, text: &'t str) -> Cow<'t, str> {
+		if preset == RedactionPreset::TempPath
+			&& let Some(roots) = crate::temp::created_roots_regex()
+		{
+			let text = self.replace(preset, &roots, text);
+			return Cow::Owned(self.replace(preset, preset.regex(), &text).into_owned());
+		}
+		self.replace(preset, preset.regex(), text)
+	}
+
+	fn replace<'t>(&mut self, preset: RedactionPreset, regex: &Regex, text: &'t str) -> Cow<'t, str> {
+		regex.replace_all(text, |caps: &Captures| {
+			let value = &caps[0];
+			if !preset.accepts(value) {
+				return value.to_owned();
+			}
+			let next = self.counts.get(&preset).copied().unwrap_or(0) + 1;
+			let number = *self.numbers.entry((preset, value.to_owned())).or_insert_with(|| {
+				self.counts.insert(preset, next);
+				next
+			});
+			format!("[{}-{number}]", preset.label())
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn redact(preset: RedactionPreset, text: &str) -> String {
+		PresetNumbering::default().apply(preset, text).into_owned()
+	}
+
+	#[test]
+	fn test_numbering_is_stable() {
+		let text = "a=67e55044-10b1-426f-9247-bb680e5fe0c8 b=0e4e1c5a-9c1f-4b56-8f7e-2b3c4d5e6f70 c=67e55044-10b1-426f-9247-bb680e5fe0c8";
+		assert_eq!(redact(RedactionPreset::Uuid, text), "a=[UUID-1] b=[UUID-2] c=[UUID-1]");
+	}
+
+	#[test]
+	fn test_presets() {
+		use RedactionPreset::*;
+
+		assert_eq!(redact(Timestamp, "at 2026-01-22T12:00:00.123+01:00 and 2026-01-22 12:00"), "at [TIMESTAMP-1] and [TIMESTAMP-2]");
+		assert_eq!(redact(UnixEpoch, "t=1769083200 ms=1769083200123 n=12345"), "t=[EPOCH-1] ms=[EPOCH-2] n=12345");
+		assert_eq!(
+			redact(Duration, "took 42ms, then 1m30s and 1.5s; v2 stays"),
+			"took [DURATION-1], then [DURATION-2] and [DURATION-3]; v2 stays"
+		);
+		assert_eq!(redact(HexHash, "commit a0f7d74 (1234567, deadbeef, abc)"), "commit [HASH-1] (1234567, deadbeef, abc)");
+		let temp = std::env::temp_dir().join("v_fixture_a1B2");
+		assert_eq!(redact(TempPath, &format!("wrote {}/out.txt", temp.display())), "wrote [TEMP-1]/out.txt");
+	}
+}
@@ -0,0 +1,135 @@
+//! insta integration: [`assert_fixture_snapshot!`](crate::assert_fixture_snapshot).
+//!
+//! Enabled by the `insta` feature. The macro accepts a [`Fixture`], a
+//! [`TempFixture`] (snapshotting its current disk state) or a configured
+//! [`FixtureRenderer`], names the snapshot after the test like
+//! `insta::assert_snapshot!` does, and attaches the snapshotted file paths as
+//! snapshot metadata.
+
+#[doc(hidden)]
+pub use insta;
+use insta::internals::Content;
+
+use crate::{Fixture, FixtureRenderer, TempFixture};
+
+/// Something [`assert_fixture_snapshot!`](crate::assert_fixture_snapshot) can snapshot.
+pub trait FixtureSnapshot {
+	/// Render the snapshot text, returning it with the paths of the rendered files.
+	fn render_snapshot(self) -> (String, Vec<String>);
+}
+
+impl FixtureSnapshot for FixtureRenderer<'_> {
+	fn render_snapshot(self) -> (String, Vec<String>) {
+		let paths = self.rendered_paths();
+		(self.render(), paths)
+	}
+}
+
+impl FixtureSnapshot for &Fixture {
+	fn render_snapshot(self) -> (String, Vec<String>) {
+		FixtureRenderer::new(self).render_snapshot()
+	}
+}
+
+impl FixtureSnapshot for Fixture {
+	fn render_snapshot(self) -> (String, Vec<String>) {
+		(&self).render_snapshot()
+	}
+}
+
+impl FixtureSnapshot for &TempFixture {
+	fn render_snapshot(self) -> (String, Vec<String>) {
+		self.read_all_from_disk().render_snapshot()
+	}
+}
+
+impl FixtureSnapshot for TempFixture {
+	fn render_snapshot(self) -> (String, Vec<String>) {
+		(&self).render_snapshot()
+	}
+}
+
+/// Render `value` and build the insta settings carrying its file list.
+#[doc(hidden)]
+pub fn __prepare(value: impl FixtureSnapshot) -> (String, insta::Settings) {
+	let (rendered, paths) = value.render_snapshot();
+	let mut settings = insta::Settings::clone_current();
+	let files = Content::Seq(paths.into_iter().map(Content::String).collect());
+	settings.set_raw_info(&Content::Map(vec![(Content::from("files"), files)]));
+	(rendered, settings)
+}
+
+/// Snapshot a [`Fixture`], [`TempFixture`] or [`FixtureRenderer`] with insta.
+///
+/// Fixtures and temp fixtures render with default settings; pass a configured
+/// renderer to apply its normalizations. The snapshot's `info` metadata lists
+/// the rendered file paths.
+///
+/// Like `insta::assert_snapshot!`, the snapshot is named after the test and
+/// stored in a `.snap` file, unless a name or an inline `@"..."` snapshot is given.
+///
+/// ```ignore
+/// use v_fixtures::{Fixture, FixtureRenderer, assert_fixture_snapshot};
+///
+/// let temp = fixture.write_to_tempdir();
+/// // ... run your tool ...
+/// assert_fixture_snapshot!(&temp);
+/// assert_fixture_snapshot!("named", FixtureRenderer::new(&result).normalize_git_hashes());
+/// assert_fixture_snapshot!(&result, @"...");
+/// ```
+#[macro_export]
+macro_rules! assert_fixture_snapshot {
+	($value:expr, @$snapshot:literal $(,)?) => {{
+		let (rendered, settings) = $crate::snapshot::__prepare($value);
+		let _guard = settings.bind_to_scope();
+		$crate::snapshot::insta::assert_snapshot!(rendered, @$snapshot);
+	}};
+	($name:expr, $value:expr $(,)?) => {{
+		let (rendered, settings) = $crate::snapshot::__prepare($value);
+		let _guard = settings.bind_to_scope();
+		$crate::snapshot::insta::assert_snapshot!($name, rendered);
+	}};
+	($value:expr $(,)?) => {{
+		let (rendered, settings) = $crate::snapshot::__prepare($value);
+		let _guard = settings.bind_to_scope();
+		$crate::snapshot::insta::assert_snapshot!(rendered);
+	}};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn fixture() -> Fixture {
+		Fixture::parse(
+			r#"
+//- /src/main.rs
+fn main() {}
+//- /Cargo.toml
+[package]
+"#,
+		)
+	}
+
+	#[test]
+	fn test_render_snapshot_paths() {
+		let (rendered, paths) = FixtureRenderer::new(&fixture()).regex("^/src/").render_snapshot();
+		assert_eq!(rendered, "fn main() {}\n");
+		assert_eq!(paths, ["/src/main.rs"]);
+
+		let temp = fixture().write_to_tempdir();
+		let (_, paths) = (&temp).render_snapshot();
+		assert_eq!(paths, ["/Cargo.toml", "/src/main.rs"]);
+	}
+
+	#[test]
+	fn test_assert_fixture_snapshot_inline() {
+		crate::assert_fixture_snapshot!(fixture(), @r"
+		//- /src/main.rs
+		fn main() {}
+		//- /Cargo.toml
+		[package]
+		");
+		crate::assert_fixture_snapshot!(FixtureRenderer::new(&fixture()).regex("toml"), @"[package]");
+	}
+}
@@ -0,0 +1,258 @@
+//! Semantic comparison and canonical rendering of JSON, TOML and YAML files.
+//!
+//! Structured files compare equal when they parse to the same value, so key
+//! order, indentation and quoting style don't cause failures. The mode comes
+//! from a `compare:` meta attribute (`//- /config.json compare:json`) or, when
+//! the format's feature is enabled, from the file extension. `compare:text`
+//! forces exact comparison.
+//!
+//! The parsers live behind the `json`, `toml` and `yaml` features.
+
+use crate::FixtureFile;
+
+/// How a file's content is compared and canonicalized.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CompareMode {
+	/// Exact text comparison
+	Text,
+	Json,
+	Toml,
+	Yaml,
+}
+
+impl CompareMode {
+	/// Parse the value of a `compare:` meta attribute.
+	pub fn from_name(name: &str) -> Option<Self> {
+		match name {
+			"text" | "exact" => Some(Self::Text),
+			"json" => Some(Self::Json),
+			"toml" => Some(Self::Toml),
+			"yaml" | "yml" => Some(Self::Yaml),
+			_ => None,
+		}
+	}
+
+	/// Name used in the `compare:` meta attribute.
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Text => "text",
+			Self::Json => "json",
+			Self::Toml => "toml",
+			Self::Yaml => "yaml",
+		}
+	}
+
+	/// Structured mode implied by the extension of `path`, regardless of enabled features.
+	pub fn from_extension(path: &str) -> Option<Self> {
+		let (_, extension) = path.rsplit_once('.')?;
+		match extension {
+			"json" => Some(Self::Json),
+			"toml" => Some(Self::Toml),
+			"yaml" | "yml" => Some(Self::Yaml),
+			_ => None,
+		}
+	}
+
+	/// Mode for `file`: its `compare:` attribute, otherwise its extension, if the
+	/// format's feature is enabled; otherwise [`CompareMode::Text`].
+	///
+	/// Parsing a `compare:` attribute for a disabled format already panics, so
+	/// this only falls back for [`FileMeta`](crate::FileMeta) built in code.
+	pub fn for_file(file: &FixtureFile) -> Self {
+		file.meta.compare.or_else(|| Self::from_extension(&file.path)).filter(|mode| mode.enabled()).unwrap_or(Self::Text)
+	}
+
+	/// Whether the parser for this mode is compiled in.
+	pub(crate) fn enabled(self) -> bool {
+		match self {
+			Self::Text => true,
+			Self::Json => json::ENABLED,
+			Self::Toml => toml_format::ENABLED,
+			Self::Yaml => yaml::ENABLED,
+		}
+	}
+
+	/// Canonical form of `text`: keys sorted at every level, stably pretty-printed.
+	///
+	/// Returns `None` for [`CompareMode::Text`] or if `text` doesn't parse.
+	/// Panics if the format's feature is not enabled.
+	pub fn canonicalize(self, text: &str) -> Option<String> {
+		match self {
+			Self::Text => None,
+			Self::Json => json::canonicalize(text),
+			Self::Toml => toml_format::canonicalize(text),
+			Self::Yaml => yaml::canonicalize(text),
+		}
+	}
+
+	/// Whether `a` and `b` are equal under this mode.
+	///
+	/// Structured content that fails to parse on either side is compared as text.
+	pub fn equivalent(self, a: &str, b: &str) -> bool {
+		if a == b {
+			return true;
+		}
+		match (self.canonicalize(a), self.canonicalize(b)) {
+			(Some(a), Some(b)) => a == b,
+			_ => false,
+		}
+	}
+}
+
+#[cfg(feature = "json")]
+mod json {
+	use serde_json::{Map, Value};
+
+	pub const ENABLED: bool = true;
+
+	pub fn canonicalize(text: &str) -> Option<String> {
+		let value = sort(serde_json::from_str(text).ok()?);
+		let mut out = serde_json::to_string_pretty(&value).expect("failed to serialize JSON");
+		out.push('\n');
+		Some(out)
+	}
+
+	fn sort(value: Value) -> Value {
+		match value {
+			Value::Object(map) => {
+				let mut entries: Vec<_> = map.into_iter().collect();
+				entries.sort_by(|a, b| a.0.cmp(&b.0));
+				Value::Object(entries.into_iter().map(|(k, v)| (k, sort(v))).collect::<Map<_, _>>())
+			}
+			Value::Array(items) => Value::Array(items.into_iter().map(sort).collect()),
+			other => other,
+		}
+	}
+}
+
+#[cfg(not(feature = "json"))]
+mod json {
+	pub const ENABLED: bool = false;
+
+	pub fn canonicalize(_text: &str) -> Option<String> {
+		panic!("JSON comparison requires the `json` feature of v_fixtures");
+	}
+}
+
+#[cfg(feature = "toml")]
+mod toml_format {
+	use toml::{Table, Value};
+
+	pub const ENABLED: bool = true;
+
+	pub fn canonicalize(text: &str) -> Option<String> {
+		let table = sort_table(text.parse::<Table>().ok()?);
+		Some(toml::to_string_pretty(&table).expect("failed to serialize TOML"))
+	}
+
+	fn sort_table(table: Table) -> Table {
+		let mut entries: Vec<_> = table.into_iter().collect();
+		entries.sort_by(|a, b| a.0.cmp(&b.0));
+		entries.into_iter().map(|(k, v)| (k, sort(v))).collect()
+	}
+
+	fn sort(value: Value) -> Value {
+		match value {
+			Value::Table(table) => Value::Table(sort_table(table)),
+			Value::Array(items) => Value::Array(items.into_iter().map(sort).collect()),
+			other => other,
+		}
+	}
+}
+
+#[cfg(not(feature = "toml"))]
+mod toml_format {
+	pub const ENABLED: bool = false;
+
+	pub fn canonicalize(_text: &str) -> Option<String> {
+		panic!("TOML comparison requires the `toml` feature of v_fixtures");
+	}
+}
+
+#[cfg(feature = "yaml")]
+mod yaml {
+	use std::cmp::Ordering;
+
+	use serde_norway::{Mapping, Value};
+
+	pub const ENABLED: bool = true;
+
+	pub fn canonicalize(text: &str) -> Option<String> {
+		let value = sort(serde_norway::from_str(text).ok()?);
+		Some(serde_norway::to_string(&value).expect("failed to serialize YAML"))
+	}
+
+	fn sort(value: Value) -> Value {
+		match value {
+			Value::Mapping(mapping) => {
+				let mut entries: Vec<_> = mapping.into_iter().collect();
+				entries.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+				Value::Mapping(entries.into_iter().map(|(k, v)| (k, sort(v))).collect::<Mapping>())
+			}
+			Value::Sequence(items) => Value::Sequence(items.into_iter().map(sort).collect()),
+			Value::Tagged(mut tagged) => {
+				tagged.value = sort(tagged.value);
+				Value::Tagged(tagged)
+			}
+			other => other,
+		}
+	}
+}
+
+#[cfg(not(feature = "yaml"))]
+mod yaml {
+	pub const ENABLED: bool = false;
+
+	pub fn canonicalize(_text: &str) -> Option<String> {
+		panic!("YAML comparison requires the `yaml` feature of v_fixtures");
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_mode_for_file() {
+		let mut file = FixtureFile::new("/data.json".to_owned(), String::new());
+		assert_eq!(CompareMode::for_file(&file), if cfg!(feature = "json") { CompareMode::Json } else { CompareMode::Text });
+		file.meta.compare = Some(CompareMode::Text);
+		assert_eq!(CompareMode::for_file(&file), CompareMode::Text);
+		// An explicit mode without its feature compares as text instead of panicking
+		file.meta.compare = Some(CompareMode::Yaml);
+		assert_eq!(CompareMode::for_file(&file), if cfg!(feature = "yaml") { CompareMode::Yaml } else { CompareMode::Text });
+		assert_eq!(CompareMode::from_extension("/a/b.yml"), Some(CompareMode::Yaml));
+		assert_eq!(CompareMode::from_extension("/Makefile"), None);
+	}
+
+	#[cfg(feature = "json")]
+	#[test]
+	fn test_json_equivalence() {
+		let mode = CompareMode::Json;
+		assert!(mode.equivalent(r#"{"b": [1, {"y": 2, "x": 1}], "a": null}"#, "{\"a\":null,\"b\":[1,{\"x\":1,\"y\":2}]}"));
+		assert!(!mode.equivalent("[1, 2]", "[2, 1]"));
+		assert!(!mode.equivalent("{", "{ "));
+		assert_eq!(
+			mode.canonicalize(r#"{"b": 1, "a": {"d": 2, "c": 3}}"#).unwrap(),
+			"{\n  \"a\": {\n    \"c\": 3,\n    \"d\": 2\n  },\n  \"b\": 1\n}\n"
+		);
+	}
+
+	#[cfg(feature = "toml")]
+	#[test]
+	fn test_toml_equivalence() {
+		let mode = CompareMode::Toml;
+		assert!(mode.equivalent("b = 1\na = 'x'\n[t]\nk = [1, 2]\n", "a = \"x\"\nb = 1\nt = { k = [1, 2] }\n"));
+		assert!(!mode.equivalent("a = 1\n", "a = 2\n"));
+		assert_eq!(mode.canonicalize("b = 1\na = 2\n").unwrap(), "a = 2\nb = 1\n");
+	}
+
+	#[cfg(feature = "yaml")]
+	#[test]
+	fn test_yaml_equivalence() {
+		let mode = CompareMode::Yaml;
+		assert!(mode.equivalent("b: 1\na:\n  - x\n  - y\n", "{a: [x, y], b: 1}"));
+		assert!(!mode.equivalent("a: 1\n", "a: '1'\n"));
+		assert_eq!(mode.canonicalize("b: 1\na: 2\n").unwrap(), "a: 2\nb: 1\n");
+	}
+}
@@ -0,0 +1,244 @@
+//! Where and under what name fixtures are written to temporary directories.
+
+use std::{
+	collections::BTreeSet,
+	fs,
+	path::{Path, PathBuf},
+	sync::Mutex,
+};
+
+use regex::Regex;
+
+use crate::{Fixture, TempFixture};
+
+/// Environment variable setting the directory temp fixtures are created in, instead of the system temp dir.
+pub const TEMP_DIR_VAR: &str = "V_FIXTURES_TMPDIR";
+
+/// Longest test name put in a directory name; longer ones keep their end.
+const MAX_TEST_NAME_LEN: usize = 80;
+
+/// Every base directory and prefix temp fixtures were created with so far,
+/// for [`RedactionPreset::TempPath`](crate::RedactionPreset::TempPath).
+static CREATED_ROOTS: Mutex<CreatedRoots> = Mutex::new(CreatedRoots {
+	bases: BTreeSet::new(),
+	regex: None,
+});
+
+struct CreatedRoots {
+	/// Escaped `<base>/<prefix>` patterns; only grows with new bases and prefixes, not with every fixture
+	bases: BTreeSet<String>,
+	/// Matches a root below any of `bases`, built on first use after a change
+	regex: Option<Regex>,
+}
+
+/// Options for [`Fixture::write_to_tempdir_with`].
+///
+/// Directories are created in, by priority, the [`base_dir`](Self::base_dir),
+/// the directory in `V_FIXTURES_TMPDIR`, or the system temp dir. They are named
+/// after the prefix and, unless disabled, the running test (from the thread
+/// name), e.g. `v_fixture_tests__test_render_a1B2c3`.
+///
+/// # Example
+///
+/// ```
+/// use v_fixtures::{Fixture, TempDirOptions};
+///
+/// let base = tempfile::tempdir().unwrap();
+/// let temp = Fixture::parse("//- /a.txt\na\n").write_to_tempdir_with(&TempDirOptions::new().base_dir(base.path()).prefix("render_"));
+/// assert!(temp.root.starts_with(base.path()));
+/// assert!(temp.root.file_name().unwrap().to_str().unwrap().starts_with("render_"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TempDirOptions {
+	base_dir: Option<PathBuf>,
+	prefix: String,
+	test_name: bool,
+	cwd: Option<String>,
+	placeholders: bool,
+}
+
+impl Default for TempDirOptions {
+	fn default() -> Self {
+		Self {
+			base_dir: None,
+			prefix: "v_fixture_".to_owned(),
+			test_name: true,
+			cwd: None,
+			placeholders: true,
+		}
+	}
+}
+
+impl TempDirOptions {
+	/// Options for a `v_fixture_`-prefixed, test-named directory in the default location.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Create directories in `dir` (created if missing), e.g. on a tmpfs or inside `target/`.
+	///
+	/// A relative path is resolved against the current directory.
+	pub fn base_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+		self.base_dir = Some(dir.into());
+		self
+	}
+
+	/// Start directory names with `prefix` instead of `v_fixture_`.
+	pub fn prefix(mut self, prefix: &str) -> Self {
+		self.prefix = prefix.to_owned();
+		self
+	}
+
+	/// Don't put the running test's name in directory names.
+	pub fn without_test_name(mut self) -> Self {
+		self.test_name = false;
+		self
+	}
+
+	/// Write `{{ROOT}}` and `{{CWD}}` in fixture files literally instead of expanding them.
+	pub fn without_placeholders(mut self) -> Self {
+		self.placeholders = false;
+		self
+	}
+
+	/// Set the fixture's working directory, relative to its root, like [`TempFixture::cwd`].
+	///
+	/// Unlike setting it afterwards, `{{CWD}}` in the written files expands to
+	/// this directory rather than the root.
+	pub fn cwd(mut self, path: &str) -> Self {
+		self.cwd = Some(path.to_owned());
+		self
+	}
+
+	/// Directory to create temp fixtures in, as an absolute path.
+	fn resolve_base_dir(&self) -> PathBuf {
+		let dir = match &self.base_dir {
+			Some(dir) => dir.clone(),
+			None => env_base_dir().unwrap_or_else(std::env::temp_dir),
+		};
+		std::path::absolute(&dir).unwrap_or_else(|e| panic!("invalid temp dir base {}: {e}", dir.display()))
+	}
+
+	/// Prefix of the directory name, with the test name if enabled and known.
+	fn full_prefix(&self) -> String {
+		match self.test_name.then(current_test_name).flatten() {
+			Some(name) => format!("{}{name}_", self.prefix),
+			None => self.prefix.clone(),
+		}
+	}
+}
+
+impl Fixture {
+	/// Write fixture files to a temporary directory placed and named according to `options`.
+	pub fn write_to_tempdir_with(&self, options: &TempDirOptions) -> TempFixture {
+		let base = options.resolve_base_dir();
+		fs::create_dir_all(&base).unwrap_or_else(|e| panic!("failed to create temp dir base {}: {e}", base.display()));
+		let temp_dir = tempfile::Builder::new().prefix(&options.full_prefix()).tempdir_in(&base).expect("failed to create temp dir");
+		register_root(&base, &options.prefix);
+
+		let mut temp = TempFixture::new(temp_dir.path().to_path_buf(), temp_dir, self.files.clone());
+		if let Some(cwd) = &options.cwd {
+			temp = temp.cwd(cwd);
+			temp.written_cwd = temp.cwd.clone();
+		}
+		if options.placeholders {
+			self.expand_placeholders(&temp.root, &temp.effective_cwd()).write_to_directory(&temp.root);
+		} else {
+			self.write_to_directory(&temp.root);
+		}
+		temp
+	}
+}
+
+/// The base directory set through `V_FIXTURES_TMPDIR`, if any.
+fn env_base_dir() -> Option<PathBuf> {
+	std::env::var_os(TEMP_DIR_VAR).filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+/// Remember that roots named `<prefix>...` are created in `base`, as given and
+/// canonicalized, so their paths can be redacted.
+fn register_root(base: &Path, prefix: &str) {
+	let mut created = CREATED_ROOTS.lock().unwrap_or_else(|e| e.into_inner());
+	let canonical = base.canonicalize().ok().filter(|canonical| canonical != base);
+	for base in [Some(base.to_path_buf()), canonical].into_iter().flatten() {
+		let base = regex::escape(base.to_string_lossy().trim_end_matches(['/', '\\']));
+		if created.bases.insert(format!(r"{base}[/\\]{}", regex::escape(prefix))) {
+			created.regex = None;
+		}
+	}
+}
+
+/// A regex matching the root of any temp fixture created so far, if there is one.
+pub(crate) fn created_roots_regex() -> Option<Regex> {
+	let mut created = CREATED_ROOTS.lock().unwrap_or_else(|e| e.into_inner());
+	if created.bases.is_empty() {
+		return None;
+	}
+	if created.regex.is_none() {
+		// Test names and the random suffix only use these characters
+		let bases: Vec<&str> = created.bases.iter().map(String::as_str).collect();
+		created.regex = Some(Regex::new(&format!(r"(?:{})[A-Za-z0-9_-]*", bases.join("|"))).unwrap());
+	}
+	created.regex.clone()
+}
+
+/// Name of the running test, made safe for a file name.
+///
+/// The test harness names each test's thread after the test's path; a thread
+/// named `main` (tests run with `--test-threads=1`) or without a name gives `None`.
+fn current_test_name() -> Option<String> {
+	let thread = std::thread::current();
+	let name = thread.name().filter(|name| *name != "main")?;
+	Some(sanitize(name))
+}
+
+fn sanitize(name: &str) -> String {
+	let name: String = name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect();
+	let start = name.len().saturating_sub(MAX_TEST_NAME_LEN);
+	name[start..].to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_named_after_running_test() {
+		let temp = Fixture::parse("a\n").write_to_tempdir();
+		let name = temp.root.file_name().unwrap().to_string_lossy().into_owned();
+		// Single-threaded test runs use the main thread, which carries no test name
+		if std::thread::current().name() != Some("main") {
+			assert!(name.starts_with("v_fixture_temp__tests__test_named_after_running_test_"), "{name}");
+		}
+		let base = env_base_dir().unwrap_or_else(std::env::temp_dir);
+		assert!(temp.root.starts_with(std::path::absolute(base).unwrap()));
+
+		let temp = Fixture::parse("a\n").write_to_tempdir_with(&TempDirOptions::new().without_test_name());
+		assert!(!temp.root.to_string_lossy().contains("test_named"));
+
+		// A custom prefix through the older API is kept exactly
+		let temp = Fixture::parse("a\n").write_to_tempdir_with_prefix("exact_");
+		let name = temp.root.file_name().unwrap().to_string_lossy().into_owned();
+		assert!(name.starts_with("exact_") && !name.contains("test_named"), "{name}");
+	}
+
+	#[test]
+	fn test_base_dir_is_created() {
+		let outer = tempfile::tempdir().unwrap();
+		let base = outer.path().join("nested/base");
+		let temp = Fixture::parse("a\n").write_to_tempdir_with(&TempDirOptions::new().base_dir(&base).prefix("x_"));
+		assert_eq!(temp.root.parent().unwrap(), base);
+		assert_eq!(temp.read("/main.rs"), "a\n");
+
+		// The whole root is redacted, though it isn't directly in the system temp dir
+		let text = format!("{}/out.txt\n", temp.root.display());
+		let rendered = crate::FixtureRenderer::new(&Fixture::parse(&text)).redact_preset(crate::RedactionPreset::TempPath).render();
+		assert_eq!(rendered, "[TEMP-1]/out.txt\n");
+	}
+
+	#[test]
+	fn test_sanitize() {
+		assert_eq!(sanitize("tests::test_a"), "tests__test_a");
+		assert_eq!(sanitize(&"a".repeat(100)).len(), MAX_TEST_NAME_LEN);
+	}
+}
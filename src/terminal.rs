@@ -0,0 +1,141 @@
+//! Normalization of captured terminal output: ANSI escape sequences, `\r`
+//! overwrites and trailing whitespace, see
+//! [`FixtureRenderer::strip_ansi`](crate::FixtureRenderer::strip_ansi) and friends.
+
+use std::{borrow::Cow, sync::LazyLock};
+
+use regex::Regex;
+
+/// CSI sequences (colors, cursor movement, erasing), OSC sequences (titles, hyperlinks), charset selection and two-byte escapes.
+const ANSI_PATTERN: &str = r"\x1b(?:\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(?:\x07|\x1b\\)|[()][0-9A-Za-z]|[@-Z\\-_])";
+
+static ANSI: LazyLock<Regex> = LazyLock::new(|| Regex::new(ANSI_PATTERN).unwrap());
+
+/// [`ANSI`] anchored at the start, so trying it at every char stays linear.
+static ANSI_AT_START: LazyLock<Regex> = LazyLock::new(|| Regex::new(&format!("^{ANSI_PATTERN}")).unwrap());
+
+/// Remove ANSI escape sequences from `text`.
+pub(crate) fn strip_ansi(text: &str) -> Cow<'_, str> {
+	ANSI.replace_all(text, "")
+}
+
+/// Resolve `\r` the way a terminal would: text after it overwrites the start of the line.
+///
+/// `\r\n` line endings are kept. Erase-in-line sequences (`ESC[K`, `ESC[1K`,
+/// `ESC[2K`) are applied; other escape sequences are kept with the character
+/// they precede.
+pub(crate) fn resolve_carriage_returns(text: &str) -> Cow<'_, str> {
+	let has_overwrite = text.split('\n').any(|line| line.strip_suffix('\r').unwrap_or(line).contains('\r'));
+	if !has_overwrite {
+		return Cow::Borrowed(text);
+	}
+	let mut out = String::with_capacity(text.len());
+	for line in text.split_inclusive('\n') {
+		let (content, ending) = match line.strip_suffix("\r\n") {
+			Some(content) => (content, "\r\n"),
+			None => line.strip_suffix('\n').map_or((line, ""), |content| (content, "\n")),
+		};
+		out.push_str(&resolve_line(content));
+		out.push_str(ending);
+	}
+	Cow::Owned(out)
+}
+
+/// Replay a single line onto a row of cells, each a visible char with any escape
+/// sequences before it, or `None` once erased.
+fn resolve_line(line: &str) -> String {
+	let mut cells: Vec<Option<String>> = Vec::new();
+	let mut cursor = 0;
+	let mut pending = String::new();
+	let mut rest = line;
+	while let Some(c) = rest.chars().next() {
+		if let Some(escape) = (c == '\x1b').then(|| ANSI_AT_START.find(rest)).flatten() {
+			match erase_in_line(escape.as_str()) {
+				Some(0) => cells.truncate(cursor),
+				Some(1) => cells.iter_mut().take(cursor + 1).for_each(|cell| *cell = None),
+				Some(_) => cells.iter_mut().for_each(|cell| *cell = None),
+				None => pending.push_str(escape.as_str()),
+			}
+			rest = &rest[escape.end()..];
+			continue;
+		}
+		rest = &rest[c.len_utf8()..];
+		if c == '\r' {
+			cursor = 0;
+			continue;
+		}
+		pending.push(c);
+		let cell = Some(std::mem::take(&mut pending));
+		match cells.get_mut(cursor) {
+			Some(existing) => *existing = cell,
+			None => cells.push(cell),
+		}
+		cursor += 1;
+	}
+	while cells.last().is_some_and(Option::is_none) {
+		cells.pop();
+	}
+	let mut out: String = cells.iter().map(|cell| cell.as_deref().unwrap_or(" ")).collect();
+	out.push_str(&pending);
+	out
+}
+
+/// The mode of an erase-in-line sequence (`ESC[nK`), if `escape` is one.
+fn erase_in_line(escape: &str) -> Option<u8> {
+	let param = escape.strip_prefix("\x1b[")?.strip_suffix('K')?;
+	match param {
+		"" | "0" => Some(0),
+		"1" => Some(1),
+		"2" => Some(2),
+		_ => None,
+	}
+}
+
+/// Remove spaces and tabs at the end of every line, keeping line endings.
+pub(crate) fn trim_trailing_whitespace(text: &str) -> Cow<'_, str> {
+	let trim = |line: &str| line.trim_end_matches([' ', '\t']).len() != line.len();
+	let lines = || {
+		text.split_inclusive('\n').map(|line| {
+			let ending_len = if line.ends_with("\r\n") { 2 } else { usize::from(line.ends_with('\n')) };
+			line.split_at(line.len() - ending_len)
+		})
+	};
+	if !lines().any(|(content, _)| trim(content)) {
+		return Cow::Borrowed(text);
+	}
+	Cow::Owned(lines().flat_map(|(content, ending)| [content.trim_end_matches([' ', '\t']), ending]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_strip_ansi() {
+		let text = "\x1b[1;32mok\x1b[0m \x1b]8;;https://example.com\x07link\x1b]8;;\x07 \x1b(Bdone\n";
+		assert_eq!(strip_ansi(text), "ok link done\n");
+		assert!(matches!(strip_ansi("plain\n"), Cow::Borrowed(_)));
+	}
+
+	#[test]
+	fn test_resolve_carriage_returns() {
+		assert_eq!(resolve_carriage_returns("10%\r50%\r100%\ndone\n"), "100%\ndone\n");
+		// Shorter text only overwrites the start, unless the line is erased
+		assert_eq!(resolve_carriage_returns("downloading\rdone\n"), "doneloading\n");
+		assert_eq!(resolve_carriage_returns("downloading\r\x1b[Kdone\n"), "done\n");
+		assert_eq!(resolve_carriage_returns("downloading\r\x1b[2Kdone\n"), "done\n");
+		// Colors stay attached to the characters that survive
+		assert_eq!(resolve_carriage_returns("\x1b[31mwait\r\x1b[32mok!!\x1b[0m\n"), "\x1b[32mok!!\x1b[0m\n");
+		// Long progress lines resolve in linear time
+		let long = format!("{}\rdone\n", "#".repeat(200_000));
+		assert_eq!(resolve_carriage_returns(&long).len(), long.len() - 5);
+		let crlf = "a\r\nb\r\n";
+		assert!(matches!(resolve_carriage_returns(crlf), Cow::Borrowed(t) if t == crlf));
+	}
+
+	#[test]
+	fn test_trim_trailing_whitespace() {
+		assert_eq!(trim_trailing_whitespace("a  \nb\t\r\nc \n  "), "a\nb\r\nc\n");
+		assert!(matches!(trim_trailing_whitespace("a\n b\n"), Cow::Borrowed(_)));
+	}
+}
@@ -0,0 +1,130 @@
+//! `tree`-style listing of a fixture's files, see [`FixtureRenderer::tree`](crate::FixtureRenderer::tree).
+
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use crate::FixtureFile;
+
+/// A path component: a file, a directory, or both when fixture paths conflict (`/a` and `/a/b`).
+#[derive(Default)]
+struct Node<'a> {
+	file: Option<(&'a FixtureFile, usize)>,
+	children: BTreeMap<&'a str, Node<'a>>,
+}
+
+/// Render a listing of `files` with their sizes in bytes, modes and link targets.
+///
+/// A path that is both a file and a directory is listed as both, with the file marked.
+pub(crate) fn render_tree<'a>(files: impl IntoIterator<Item = (&'a FixtureFile, usize)>) -> String {
+	let mut root = Node::default();
+	for (file, size) in files {
+		let node = file
+			.path
+			.split('/')
+			.filter(|c| !c.is_empty())
+			.fold(&mut root, |node, component| node.children.entry(component).or_default());
+		node.file = Some((file, size));
+	}
+
+	let mut out = ".\n".to_owned();
+	render_dir(&root.children, "", &mut out);
+	out
+}
+
+fn render_dir(dir: &BTreeMap<&str, Node<'_>>, prefix: &str, out: &mut String) {
+	// A conflicting name takes two lines, the file's and the directory's
+	let lines: Vec<(&str, &Node<'_>, bool)> = dir
+		.iter()
+		.flat_map(|(name, node)| {
+			let file = node.file.is_some().then_some((*name, node, false));
+			let dir = (!node.children.is_empty()).then_some((*name, node, true));
+			file.into_iter().chain(dir)
+		})
+		.collect();
+	for (idx, &(name, node, is_dir)) in lines.iter().enumerate() {
+		let last = idx + 1 == lines.len();
+		let (branch, indent) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+		if is_dir {
+			writeln!(out, "{prefix}{branch}{name}/").unwrap();
+			render_dir(&node.children, &format!("{prefix}{indent}"), out);
+			continue;
+		}
+		let Some((file, size)) = node.file else { continue };
+		write!(out, "{prefix}{branch}{name}").unwrap();
+		if let Some(target) = &file.meta.symlink {
+			write!(out, " -> {target}").unwrap();
+		} else {
+			let unit = if size == 1 { "byte" } else { "bytes" };
+			match file.meta.mode {
+				Some(mode) => write!(out, " ({size} {unit}, mode {mode:o})"),
+				None => write!(out, " ({size} {unit})"),
+			}
+			.unwrap();
+		}
+		if !node.children.is_empty() {
+			out.push_str(" [conflicts with directory]");
+		}
+		out.push('\n');
+	}
+}
+
+/// Initial state of [`fnv1a64_extend`].
+pub(crate) const FNV1A64_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// 64-bit FNV-1a hash, stable across platforms and releases.
+pub(crate) fn fnv1a64(bytes: &[u8]) -> u64 {
+	fnv1a64_extend(FNV1A64_OFFSET, bytes)
+}
+
+/// Continue an FNV-1a `hash` over `bytes`, for hashing input read in pieces.
+pub(crate) fn fnv1a64_extend(hash: u64, bytes: &[u8]) -> u64 {
+	bytes.iter().fold(hash, |hash, &b| (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Fixture;
+
+	#[test]
+	fn test_render_tree() {
+		let mut fixture = Fixture::parse("//- /src/main.rs\nfn main() {}\n//- /Cargo.toml\n[package]\n//- /src/bin/run.sh\nx\n//- /latest\n");
+		fixture.files[2].meta.mode = Some(0o755);
+		fixture.files[3].meta.symlink = Some("src/main.rs".to_owned());
+
+		let tree = render_tree(fixture.files.iter().map(|f| (f, f.text.len())));
+		assert_eq!(
+			tree,
+			"\
+.
+├── Cargo.toml (10 bytes)
+├── latest -> src/main.rs
+└── src/
+    ├── bin/
+    │   └── run.sh (2 bytes, mode 755)
+    └── main.rs (13 bytes)
+"
+		);
+	}
+
+	#[test]
+	fn test_render_tree_path_conflict() {
+		let fixture = Fixture::parse("//- /a\nx\n//- /a/b\ny\n//- /c\nz\n");
+		let tree = render_tree(fixture.files.iter().map(|f| (f, f.text.len())));
+		assert_eq!(
+			tree,
+			"\
+.
+├── a (2 bytes) [conflicts with directory]
+├── a/
+│   └── b (2 bytes)
+└── c (2 bytes)
+"
+		);
+	}
+
+	#[test]
+	fn test_fnv1a64() {
+		assert_eq!(fnv1a64(b""), 0xcbf2_9ce4_8422_2325);
+		assert_eq!(fnv1a64(b"a"), 0xaf63_dc4c_8601_ec8c);
+	}
+}